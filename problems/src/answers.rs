@@ -0,0 +1,89 @@
+use std::io::{self, Write};
+
+use colored::*;
+
+use crate::report::Record;
+
+// answers for the embedded inputs indexed by day - 1,
+// None means answer can't be checked automatically
+const EXPECTED: [[Option<&str>; 2]; 16] = [
+    [Some("3301059"), Some("4948732")],
+    [Some("3706713"), Some("8609")],
+    [Some("860"), Some("9238")],
+    [Some("1650"), Some("1129")],
+    [Some("[0, 0, 0, 0, 0, 0, 0, 0, 0, 6731945]"), Some("9571668")],
+    [Some("314247"), Some("514")],
+    [Some("38500"), Some("33660560")],
+    // second star answer is drawn into an image
+    [Some("828"), None],
+    [Some("2662308295"), Some("63441")],
+    [Some("267"), Some("1309")],
+    // second star answer is drawn into an image
+    [Some("2418"), None],
+    [Some("14780"), Some("279751820342592")],
+    [Some("326"), Some("15988")],
+    [Some("201324"), Some("6326857")],
+    [Some("282"), Some("286")],
+    [Some("36627552"), Some("79723033")],
+];
+
+pub(crate) fn expected(day: usize, star: usize) -> Option<&'static str> {
+    EXPECTED.get(day - 1).and_then(|stars| stars[star - 1])
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Verdict {
+    Pass,
+    Fail(String),
+    // no known answer for the given input
+    Missing,
+}
+
+pub(crate) fn verify(record: &Record, embedded_input: bool) -> Verdict {
+    // known answers are only valid for the inputs they were computed for
+    let expected = match expected(record.day, record.star) {
+        Some(expected) if embedded_input => expected,
+        _ => return Verdict::Missing,
+    };
+
+    match (&record.answer, &record.error) {
+        (Some(answer), _) if answer == expected => Verdict::Pass,
+        (Some(answer), _) => Verdict::Fail(format!("expected {}, got {}", expected, answer)),
+        (None, Some(err)) => Verdict::Fail(format!("expected {}, got error: {}", expected, err)),
+        (None, None) => Verdict::Fail(format!("expected {}, got nothing", expected)),
+    }
+}
+
+// prints verification report, returns false if any answer is wrong
+pub(crate) fn report(verdicts: &[(&Record, Verdict)], out: &mut dyn Write) -> io::Result<bool> {
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    writeln!(out, "{}", "Verification:".bold())?;
+
+    for (record, verdict) in verdicts {
+        let status = match verdict {
+            Verdict::Pass => {
+                passed += 1;
+                "pass".green().to_string()
+            }
+            Verdict::Fail(reason) => {
+                failed += 1;
+                format!("{} ({})", "FAIL".bold().red(), reason)
+            }
+            Verdict::Missing => {
+                missing += 1;
+                "missing".yellow().to_string()
+            }
+        };
+
+        writeln!(out, "day {} star {}: {}", record.day, record.star, status)?;
+    }
+
+    writeln!(
+        out,
+        "\n{} passed, {} failed, {} missing\n",
+        passed, failed, missing
+    )?;
+
+    Ok(failed == 0)
+}
@@ -0,0 +1,216 @@
+use std::{fs, path::Path, time::Instant};
+
+use colored::*;
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
+
+use utils::{StarResult, Stars};
+
+use crate::{isolation, versioned, Solver};
+
+const BASELINE_VERSION: u32 = 1;
+
+// timings of repeated runs in microseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Stats {
+    pub runs: usize,
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Stats {
+    pub(crate) fn from_samples(samples: &[f64]) -> Stats {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let runs = sorted.len();
+        let median = if runs.is_multiple_of(2) {
+            (sorted[runs / 2 - 1] + sorted[runs / 2]) / 2.0
+        } else {
+            sorted[runs / 2]
+        };
+
+        let mean = sorted.iter().sum::<f64>() / runs as f64;
+        let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / runs as f64;
+
+        Stats {
+            runs,
+            min: sorted[0],
+            median,
+            mean,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    day: usize,
+    // which stars were measured: "both", "1" or "2"
+    stars: String,
+    stats: Stats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Baseline {
+    version: u32,
+    entries: Vec<Entry>,
+}
+
+impl Baseline {
+    fn load(path: &Path) -> Result<Baseline, Error> {
+        versioned::load(path, "baseline", BASELINE_VERSION, |baseline: &Baseline| {
+            baseline.version
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|err| format_err!("Can't write baseline {}: {}", path.display(), err))
+    }
+
+    // replaces measurements of the same day and stars, keeps the rest
+    fn merge(&mut self, entries: Vec<Entry>) {
+        for entry in entries {
+            self.entries
+                .retain(|e| !(e.day == entry.day && e.stars == entry.stars));
+            self.entries.push(entry);
+        }
+        self.entries.sort_by(|a, b| (a.day, &a.stars).cmp(&(b.day, &b.stars)));
+    }
+
+    fn find(&self, day: usize, stars: &str) -> Option<&Stats> {
+        self.entries
+            .iter()
+            .find(|e| e.day == day && e.stars == stars)
+            .map(|e| &e.stats)
+    }
+}
+
+pub(crate) struct BenchOptions<'a> {
+    pub runs: usize,
+    pub stars: Stars,
+    pub save_to: Option<&'a Path>,
+    pub compare_with: Option<&'a Path>,
+    // allowed slowdown of the median in percents
+    pub threshold: f64,
+}
+
+fn stars_label(stars: Stars) -> &'static str {
+    match stars {
+        Stars::Both => "both",
+        Stars::First => "1",
+        Stars::Second => "2",
+    }
+}
+
+pub(crate) fn fmt_micros(micros: f64) -> String {
+    if micros >= 1_000_000.0 {
+        format!("{:.3}s", micros / 1_000_000.0)
+    } else if micros >= 1_000.0 {
+        format!("{:.3}ms", micros / 1_000.0)
+    } else {
+        format!("{:.1}us", micros)
+    }
+}
+
+// runs solver the given number of times, stops at the first failure
+fn measure(f: Solver, input: &str, stars: Stars, runs: usize) -> Result<Stats, Error> {
+    let mut samples = Vec::with_capacity(runs);
+
+    for _ in 0..runs {
+        let now = Instant::now();
+        let result = isolation::catch_panic(f, input, stars)?;
+        samples.push(now.elapsed().as_secs_f64() * 1_000_000.0);
+
+        for star in result.answer.stars().iter() {
+            if let StarResult::Failed(err) = star {
+                return Err(format_err!("{}", err));
+            }
+        }
+    }
+
+    Ok(Stats::from_samples(&samples))
+}
+
+// Benchmarks problems one by one, so they don't compete with each other for cores.
+// Returns false if some problems failed or became slower than the baseline.
+pub(crate) fn run(tasks: &[(Solver, &str, usize)], opts: &BenchOptions) -> Result<bool, Error> {
+    let baseline = match opts.compare_with {
+        Some(path) => Some(Baseline::load(path)?),
+        None => None,
+    };
+
+    let label = stars_label(opts.stars);
+    let mut entries = Vec::new();
+    let mut ok = true;
+
+    println!(
+        "{:>4} {:>5} {:>11} {:>11} {:>11} {:>11}   vs baseline",
+        "day", "runs", "min", "median", "mean", "stddev"
+    );
+
+    for (f, input, day) in tasks {
+        let stats = match measure(*f, input, opts.stars, opts.runs) {
+            Ok(stats) => stats,
+            Err(err) => {
+                ok = false;
+                println!("{:>4} {}: {}", day, "error".bold().red(), err);
+                continue;
+            }
+        };
+
+        let comparison = match baseline.as_ref().and_then(|b| b.find(*day, label)) {
+            None => "-".to_owned(),
+            Some(base) => {
+                let delta = (stats.median - base.median) / base.median * 100.0;
+                let text = format!("{:+.1}% ({})", delta, fmt_micros(base.median));
+
+                if delta > opts.threshold {
+                    ok = false;
+                    format!("{} {}", text, "REGRESSION".bold().red())
+                } else if delta < -opts.threshold {
+                    text.green().to_string()
+                } else {
+                    text
+                }
+            }
+        };
+
+        println!(
+            "{:>4} {:>5} {:>11} {:>11} {:>11} {:>11}   {}",
+            day,
+            stats.runs,
+            fmt_micros(stats.min),
+            fmt_micros(stats.median),
+            fmt_micros(stats.mean),
+            fmt_micros(stats.stddev),
+            comparison
+        );
+
+        entries.push(Entry {
+            day: *day,
+            stars: label.to_owned(),
+            stats,
+        });
+    }
+
+    if let Some(path) = opts.save_to {
+        let mut saved = if path.exists() {
+            Baseline::load(path)?
+        } else {
+            Baseline {
+                version: BASELINE_VERSION,
+                entries: Vec::new(),
+            }
+        };
+
+        saved.merge(entries);
+        saved.save(path)?;
+        println!("\nbaseline saved to {}", path.display());
+    }
+
+    Ok(ok)
+}
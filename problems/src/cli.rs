@@ -0,0 +1,648 @@
+use std::{path::PathBuf, time::Duration};
+
+use failure::{format_err, Error};
+
+use crate::computer::Limits;
+use crate::input::{Source, INPUT_DIR_VAR};
+use crate::report::Format;
+use crate::schedule::Schedule;
+use utils::Stars;
+
+pub(crate) const USAGE: &str = "\
+Usage: problems [DAYS...] [OPTIONS]
+       problems disasm DAY [-i PATH | --input-dir DIR]
+       problems asm PATH
+       problems net PATH [-n N]
+       problems debug DAY [-i PATH | --input-dir DIR]
+       problems exec DAY [-i PATH | --input-dir DIR]
+       problems ascii DAY [-i PATH | --input-dir DIR]
+       problems pipe DAY --init LIST [--loop] [--feed LIST] [-i PATH | --input-dir DIR]
+       problems trace DAY [-p <1|2>] [-i PATH | --input-dir DIR] [-o PATH] [--last N]
+       problems profile DAY [-p <1|2>] [-i PATH | --input-dir DIR] [--top N]
+
+Runs solutions for the selected days, all of them if no days are given.
+Days can be listed one by one or as ranges, e.g. `problems 7 12-15`.
+
+`disasm` prints the Intcode program which is the input of DAY as a list of instructions.
+`asm` turns such a list read from PATH (`-` means stdin) back into an Intcode program.
+`net` assembles the program read from PATH like `asm` does and runs it on a network of N
+machines, 50 by default, printing every packet sent. It stops once all machines halt, the
+network gets stuck or the NAT sends the same y to machine 0 twice in a row.
+`debug` starts an interactive debugger for the Intcode program of DAY, see `help` inside.
+`exec` runs the Intcode program of DAY feeding it numbers read from stdin and printing its
+output one number per line.
+`ascii` runs the Intcode program of DAY interactively, lines typed in are its input and its
+output is printed as text, values which aren't characters are printed as numbers.
+`pipe` runs copies of the Intcode program of DAY on their own threads chained one after
+another, one per value of `--init` which is the first input of its copy. `--loop` connects the
+last one back to the first, `--feed` gives more input to the first one. Prints the output of the
+last one, e.g. `pipe 7 --init 9,8,7,6,5 --loop --feed 0` runs the amplifiers of day 7.
+`trace` solves DAY printing every instruction executed by its Intcode machines to stdout or
+to the file given by `-o`, `--last N` keeps only the last N instructions.
+`profile` solves DAY counting instructions executed by its Intcode machines per opcode and per
+address, and memory reads and writes per cell. Prints the totals, the N hottest regions of the
+program disassembled and the N most accessed cells, 5 by default.
+
+Options:
+    -p, --part <1|2>        compute only the first or the second star
+    -i, --input <PATH>      read input for a single selected day from PATH, `-` means stdin
+    --input-dir <DIR>       read inputs from DIR/day<N>.txt, days without a file use
+                            the embedded input; defaults to $AOC_INPUT_DIR
+    -f, --format <FORMAT>   output format: text (default), json (one object per line) or csv
+    -j, --jobs <N>          number of problems running at once, number of physical
+                            cores by default
+    --schedule <ORDER>      order in which problems start: in-order (default) or
+                            longest-first, the latter requires `--timings`
+    --timings <PATH>        read run times of previous runs from PATH and store run
+                            times of this run there
+    -t, --timeout <SECS>    fail problems which run longer than SECS seconds
+    --max-instructions <N>  fail Intcode machines which execute more than N instructions
+    --detect-loops          fail Intcode machines which get into the same state twice
+                            without any input or output in between, slows them down
+    --bench <N>             run every selected problem N times one after another and
+                            print timing statistics; can't be combined with `--format`,
+                            `--jobs`, `--schedule`, `--timings`, `--timeout`,
+                            `--progress` or `--verify`
+    --save-baseline <PATH>  store benchmark results in PATH
+    --baseline <PATH>       compare benchmark results with PATH, exit with non-zero code
+                            if some problem got slower
+    --threshold <PCT>       slowdown of the median considered a regression, 10 by default
+    --progress              report problems on stderr as soon as they finish, results
+                            are printed in day order once all of them are done
+    --verify                compare answers with the known ones, exit with non-zero code
+                            if any of them is wrong
+    -h, --help              print this message";
+
+pub(crate) struct Options {
+    pub days: Vec<usize>,
+    pub stars: Stars,
+    pub input: Source,
+    pub format: Format,
+    pub verify: bool,
+    pub progress: bool,
+    pub timeout: Option<Duration>,
+    // applied to every Intcode machine solutions create
+    pub limits: Limits,
+    pub jobs: usize,
+    pub schedule: Schedule,
+    pub timings: Option<PathBuf>,
+    pub bench: Option<BenchSettings>,
+}
+
+pub(crate) struct BenchSettings {
+    pub runs: usize,
+    pub save_to: Option<PathBuf>,
+    pub compare_with: Option<PathBuf>,
+    pub threshold: f64,
+}
+
+pub(crate) struct TraceOptions {
+    pub day: usize,
+    pub stars: Stars,
+    pub input: Source,
+    pub output: Option<PathBuf>,
+    // keep only the given number of the last events
+    pub last: Option<usize>,
+}
+
+pub(crate) struct PipeOptions {
+    pub day: usize,
+    pub input: Source,
+    // the first input of every machine, one machine per value
+    pub init: Vec<isize>,
+    // connect the last machine to the first one
+    pub feedback: bool,
+    // input of the first machine following its initial one
+    pub feed: Vec<isize>,
+}
+
+pub(crate) struct ProfileOptions {
+    pub day: usize,
+    pub stars: Stars,
+    pub input: Source,
+    // number of hot spots and memory cells to report
+    pub top: usize,
+}
+
+pub(crate) enum Command {
+    Run(Options),
+    Disasm { day: usize, input: Source },
+    Asm { input: Source },
+    Net { input: Source, size: usize },
+    Debug { day: usize, input: Source },
+    Exec { day: usize, input: Source },
+    Ascii { day: usize, input: Source },
+    Pipe(PipeOptions),
+    Trace(TraceOptions),
+    Profile(ProfileOptions),
+    Help,
+}
+
+fn parse_day(s: &str, max_day: usize) -> Result<usize, Error> {
+    let day = s
+        .parse::<usize>()
+        .map_err(|_| format_err!("Invalid day `{}`", s))?;
+
+    if day == 0 || day > max_day {
+        return Err(format_err!("Day {} is out of range 1-{}", day, max_day));
+    }
+
+    Ok(day)
+}
+
+fn parse_days(s: &str, max_day: usize) -> Result<Vec<usize>, Error> {
+    // single day "7" or inclusive range "12-15"
+    match s.find('-') {
+        Some(pos) => {
+            let from = parse_day(&s[..pos], max_day)?;
+            let to = parse_day(&s[pos + 1..], max_day)?;
+
+            if from > to {
+                return Err(format_err!("Invalid range `{}`", s));
+            }
+
+            Ok((from..=to).collect())
+        }
+        None => Ok(vec![parse_day(s, max_day)?]),
+    }
+}
+
+fn parse_part(s: &str) -> Result<Stars, Error> {
+    match s {
+        "1" => Ok(Stars::First),
+        "2" => Ok(Stars::Second),
+        _ => Err(format_err!("Part must be either 1 or 2, got `{}`", s)),
+    }
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, Error> {
+    // too large values don't fit into Duration
+    match s.parse::<f64>().map(Duration::try_from_secs_f64) {
+        Ok(Ok(timeout)) if timeout > Duration::ZERO => Ok(timeout),
+        _ => Err(format_err!("Timeout must be a positive number of seconds, got `{}`", s)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str, name: &str) -> Result<T, Error> {
+    s.parse::<T>()
+        .map_err(|_| format_err!("Invalid value `{}` for `{}`", s, name))
+}
+
+// values separated by commas, e.g. "9,8,7"
+fn parse_list(s: &str, name: &str) -> Result<Vec<isize>, Error> {
+    s.split(',').map(|value| parse_number(value, name)).collect()
+}
+
+fn parse_input(s: &str) -> Source {
+    if s == "-" {
+        Source::Stdin
+    } else {
+        Source::File(PathBuf::from(s))
+    }
+}
+
+// splits "--name=value" into its parts, other arguments are left intact
+fn split_arg(arg: &str) -> (&str, Option<&str>) {
+    match arg.find('=') {
+        Some(pos) if arg.starts_with("--") => (&arg[..pos], Some(&arg[pos + 1..])),
+        _ => (arg, None),
+    }
+}
+
+// option value is either glued with "=" or is the next argument
+fn option_value(
+    name: &str,
+    inline_value: Option<&str>,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<String, Error> {
+    match inline_value {
+        Some(value) => Ok(value.to_owned()),
+        None => args
+            .next()
+            .ok_or_else(|| format_err!("Missing value for `{}`", name)),
+    }
+}
+
+// day, part and input of commands working with the Intcode program of a single day
+struct DayArgs {
+    day: usize,
+    stars: Stars,
+    input: Source,
+}
+
+// Parses the day, `-i`, `--input-dir` and, for commands which can compute a single star,
+// `-p`. Options specific to the command go to `option` along with a way to get their
+// value, it returns false for options it doesn't know. None means help was requested.
+fn parse_day_args(
+    mut args: impl Iterator<Item = String>,
+    max_day: usize,
+    command: &str,
+    parts: bool,
+    mut option: impl FnMut(&str, &mut dyn FnMut() -> Result<String, Error>) -> Result<bool, Error>,
+) -> Result<Option<DayArgs>, Error> {
+    let mut day = None;
+    let mut stars = Stars::Both;
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_arg(&arg);
+        let mut value = || option_value(name, inline_value, &mut args);
+
+        match name {
+            "-h" | "--help" => return Ok(None),
+
+            "-p" | "--part" if parts => stars = parse_part(&value()?)?,
+
+            "-i" | "--input" => input = Some(parse_input(&value()?)),
+
+            "--input-dir" => input = Some(Source::Dir(PathBuf::from(value()?))),
+
+            s if s.starts_with('-') => {
+                if !option(s, &mut value)? {
+                    return Err(format_err!("Unknown option `{}`", s));
+                }
+            }
+
+            s if day.is_some() => return Err(format_err!("Unexpected argument `{}`", s)),
+
+            s => day = Some(parse_day(s, max_day)?),
+        }
+    }
+
+    match day {
+        Some(day) => Ok(Some(DayArgs {
+            day,
+            stars,
+            input: Source::resolve(input),
+        })),
+        None => Err(format_err!("`{}` requires a day", command)),
+    }
+}
+
+// Arguments of commands which take nothing but the day and its input.
+// Commands which talk to the user through stdin can't read the program from there.
+fn parse_program_args(
+    args: impl Iterator<Item = String>,
+    max_day: usize,
+    command: &str,
+    stdin_allowed: bool,
+    make: fn(usize, Source) -> Command,
+) -> Result<Command, Error> {
+    let args = match parse_day_args(args, max_day, command, false, |_, _| Ok(false))? {
+        Some(args) => args,
+        None => return Ok(Command::Help),
+    };
+
+    if let (Source::Stdin, false) = (&args.input, stdin_allowed) {
+        return Err(format_err!("`{}` can't read the program from stdin", command));
+    }
+
+    Ok(make(args.day, args.input))
+}
+
+fn parse_trace_args(args: impl Iterator<Item = String>, max_day: usize) -> Result<Command, Error> {
+    let mut output = None;
+    let mut last = None;
+
+    let args = parse_day_args(args, max_day, "trace", true, |name, value| {
+        match name {
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "--last" => last = Some(parse_number::<usize>(&value()?, name)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+
+    Ok(match args {
+        Some(DayArgs { day, stars, input }) => Command::Trace(TraceOptions {
+            day,
+            stars,
+            input,
+            output,
+            last,
+        }),
+        None => Command::Help,
+    })
+}
+
+fn parse_pipe_args(args: impl Iterator<Item = String>, max_day: usize) -> Result<Command, Error> {
+    let mut init = Vec::new();
+    let mut feedback = false;
+    let mut feed = Vec::new();
+
+    let args = parse_day_args(args, max_day, "pipe", false, |name, value| {
+        match name {
+            "--init" => init = parse_list(&value()?, name)?,
+            "--loop" => feedback = true,
+            "--feed" => feed = parse_list(&value()?, name)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+
+    let DayArgs { day, input, .. } = match args {
+        Some(args) => args,
+        None => return Ok(Command::Help),
+    };
+
+    if init.is_empty() {
+        return Err(format_err!("`pipe` requires `--init` with a value for every machine"));
+    }
+
+    Ok(Command::Pipe(PipeOptions {
+        day,
+        input,
+        init,
+        feedback,
+        feed,
+    }))
+}
+
+// number of hot spots and memory cells in the profile unless `--top` is given
+const PROFILE_TOP: usize = 5;
+
+fn parse_profile_args(
+    args: impl Iterator<Item = String>,
+    max_day: usize,
+) -> Result<Command, Error> {
+    let mut top = PROFILE_TOP;
+
+    let args = parse_day_args(args, max_day, "profile", true, |name, value| {
+        match name {
+            "--top" => top = parse_number::<usize>(&value()?, name)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+
+    Ok(match args {
+        Some(DayArgs { day, stars, input }) => Command::Profile(ProfileOptions {
+            day,
+            stars,
+            input,
+            top,
+        }),
+        None => Command::Help,
+    })
+}
+
+fn parse_asm_args(args: impl Iterator<Item = String>) -> Result<Command, Error> {
+    let mut input = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+
+            // lonely "-" is stdin
+            s if s.starts_with('-') && s != "-" => {
+                return Err(format_err!("Unknown option `{}`", s))
+            }
+
+            s if input.is_some() => return Err(format_err!("Unexpected argument `{}`", s)),
+
+            s => input = Some(parse_input(s)),
+        }
+    }
+
+    match input {
+        Some(input) => Ok(Command::Asm { input }),
+        None => Err(format_err!("`asm` requires a source file")),
+    }
+}
+
+// number of machines in the network unless `-n` is given
+const NETWORK_SIZE: usize = 50;
+
+fn parse_net_args(mut args: impl Iterator<Item = String>) -> Result<Command, Error> {
+    let mut input = None;
+    let mut size = NETWORK_SIZE;
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_arg(&arg);
+
+        let mut value = || option_value(name, inline_value, &mut args);
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+
+            "-n" => {
+                size = match parse_number::<usize>(&value()?, name)? {
+                    // addresses from 255 on are taken by the NAT
+                    size @ 1..=255 => size,
+                    _ => return Err(format_err!("Network size must be in range 1-255")),
+                }
+            }
+
+            // lonely "-" is stdin
+            s if s.starts_with('-') && s != "-" => {
+                return Err(format_err!("Unknown option `{}`", s))
+            }
+
+            s if input.is_some() => return Err(format_err!("Unexpected argument `{}`", s)),
+
+            s => input = Some(parse_input(s)),
+        }
+    }
+
+    match input {
+        Some(input) => Ok(Command::Net { input, size }),
+        None => Err(format_err!("`net` requires a source file")),
+    }
+}
+
+pub(crate) fn parse_args(
+    args: impl Iterator<Item = String>,
+    max_day: usize,
+) -> Result<Command, Error> {
+    let mut args = args.peekable();
+
+    match args.peek().map(String::as_str) {
+        Some("disasm") => {
+            args.next();
+            return parse_program_args(args, max_day, "disasm", true, |day, input| {
+                Command::Disasm { day, input }
+            });
+        }
+        Some("debug") => {
+            args.next();
+            return parse_program_args(args, max_day, "debug", false, |day, input| {
+                Command::Debug { day, input }
+            });
+        }
+        Some("exec") => {
+            args.next();
+            return parse_program_args(args, max_day, "exec", false, |day, input| {
+                Command::Exec { day, input }
+            });
+        }
+        Some("ascii") => {
+            args.next();
+            return parse_program_args(args, max_day, "ascii", false, |day, input| {
+                Command::Ascii { day, input }
+            });
+        }
+        Some("pipe") => {
+            args.next();
+            return parse_pipe_args(args, max_day);
+        }
+        Some("trace") => {
+            args.next();
+            return parse_trace_args(args, max_day);
+        }
+        Some("profile") => {
+            args.next();
+            return parse_profile_args(args, max_day);
+        }
+        Some("asm") => {
+            args.next();
+            return parse_asm_args(args);
+        }
+        Some("net") => {
+            args.next();
+            return parse_net_args(args);
+        }
+        _ => {}
+    }
+
+    let mut days = Vec::new();
+    let mut stars = Stars::Both;
+    let mut input = None;
+    let mut format = Format::Text;
+    let mut verify = false;
+    let mut progress = false;
+    let mut timeout = None;
+    let mut limits = Limits::default();
+    let mut jobs = None;
+    let mut schedule = Schedule::InOrder;
+    let mut timings = None;
+
+    let mut bench_runs = None;
+    let mut save_to = None;
+    let mut compare_with = None;
+    let mut threshold = 10.0;
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_arg(&arg);
+
+        let mut value = || option_value(name, inline_value, &mut args);
+
+        match name {
+            "-h" | "--help" => return Ok(Command::Help),
+
+            "-p" | "--part" => stars = parse_part(&value()?)?,
+
+            "-i" | "--input" => input = Some(parse_input(&value()?)),
+
+            "--input-dir" => input = Some(Source::Dir(PathBuf::from(value()?))),
+
+            "-f" | "--format" => format = Format::parse(&value()?)?,
+
+            "--verify" => verify = true,
+
+            "--progress" => progress = true,
+
+            "-t" | "--timeout" => timeout = Some(parse_timeout(&value()?)?),
+
+            "--max-instructions" => {
+                limits.budget = Some(parse_number::<u64>(&value()?, name)?)
+            }
+
+            "--detect-loops" => limits.detect_loops = true,
+
+            "-j" | "--jobs" => match parse_number::<usize>(&value()?, name)? {
+                0 => return Err(format_err!("`--jobs` requires at least one job")),
+                n => jobs = Some(n),
+            },
+
+            "--schedule" => schedule = Schedule::parse(&value()?)?,
+
+            "--timings" => timings = Some(PathBuf::from(value()?)),
+
+            "--bench" => match parse_number::<usize>(&value()?, name)? {
+                0 => return Err(format_err!("`--bench` requires at least one run")),
+                runs => bench_runs = Some(runs),
+            },
+
+            "--save-baseline" => save_to = Some(PathBuf::from(value()?)),
+
+            "--baseline" => compare_with = Some(PathBuf::from(value()?)),
+
+            "--threshold" => threshold = parse_number::<f64>(&value()?, name)?,
+
+            // lonely "-" is not an option but it makes no sense as a day either
+            s if s.starts_with('-') => return Err(format_err!("Unknown option `{}`", s)),
+
+            s => {
+                for day in parse_days(s, max_day)? {
+                    if !days.contains(&day) {
+                        days.push(day);
+                    }
+                }
+            }
+        }
+    }
+
+    if days.is_empty() {
+        days = (1..=max_day).collect();
+    }
+
+    let input = Source::resolve(input);
+
+    match input {
+        Source::File(_) | Source::Stdin if days.len() != 1 => {
+            return Err(format_err!(
+                "`--input` requires exactly one day to be selected, use `--input-dir` or ${} \
+                 for several days",
+                INPUT_DIR_VAR
+            ))
+        }
+        _ => {}
+    }
+
+    if schedule == Schedule::LongestFirst && timings.is_none() {
+        return Err(format_err!("`--schedule longest-first` requires `--timings`"));
+    }
+
+    // Benchmarks run solvers one after another on the main thread, where they can't
+    // be timed out, and print only timing statistics in their own table
+    let run_only = [
+        ("--timeout", timeout.is_some()),
+        ("--verify", verify),
+        ("--format", format != Format::Text),
+        ("--progress", progress),
+        ("--jobs", jobs.is_some()),
+        ("--schedule", schedule != Schedule::InOrder),
+        ("--timings", timings.is_some()),
+    ];
+
+    let bench = match (bench_runs, run_only.iter().find(|(_, given)| *given)) {
+        (Some(_), Some((option, _))) => {
+            return Err(format_err!("`{}` can't be used with `--bench`", option))
+        }
+        (Some(runs), None) => Some(BenchSettings {
+            runs,
+            save_to,
+            compare_with,
+            threshold,
+        }),
+        (None, _) if save_to.is_some() || compare_with.is_some() => {
+            return Err(format_err!("Baselines can only be used with `--bench`"))
+        }
+        (None, _) => None,
+    };
+
+    Ok(Command::Run(Options {
+        days,
+        stars,
+        input,
+        format,
+        verify,
+        progress,
+        timeout,
+        limits,
+        jobs: jobs.unwrap_or_else(num_cpus::get_physical),
+        schedule,
+        timings,
+        bench,
+    }))
+}
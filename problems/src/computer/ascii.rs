@@ -0,0 +1,80 @@
+use super::{Computer, Event, IntcodeError, Queue};
+
+const NEWLINE: isize = b'\n' as isize;
+
+// piece of output of an ASCII program
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Chunk {
+    Text(String),
+    // value which isn't an ASCII character, e.g. an answer
+    Value(isize),
+}
+
+// every line is terminated with a newline
+pub(crate) fn encode(line: &str) -> Vec<isize> {
+    line.bytes()
+        .map(isize::from)
+        .chain(std::iter::once(NEWLINE))
+        .collect()
+}
+
+// consecutive characters are joined into text
+pub(crate) fn decode(values: &[isize]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut text = String::new();
+
+    for value in values {
+        match value {
+            0..=127 => text.push(*value as u8 as char),
+            _ => {
+                if !text.is_empty() {
+                    chunks.push(Chunk::Text(text.split_off(0)));
+                }
+                chunks.push(Chunk::Value(*value));
+            }
+        }
+    }
+
+    if !text.is_empty() {
+        chunks.push(Chunk::Text(text));
+    }
+
+    chunks
+}
+
+// Talks to a program in lines of text instead of single values
+pub(crate) struct Ascii {
+    c: Computer,
+    input: Queue,
+}
+
+impl Ascii {
+    pub(crate) fn new(mut c: Computer) -> Self {
+        let input = Queue::new();
+        c.set_input_source(input.clone());
+
+        Ascii { c, input }
+    }
+
+    pub(crate) fn send_line(&mut self, line: &str) {
+        for value in encode(line) {
+            self.input.push(value);
+        }
+    }
+
+    // runs until the program wants more input than it was given or finishes
+    pub(crate) fn run(&mut self) -> Result<Vec<Chunk>, IntcodeError> {
+        let mut output = Vec::new();
+
+        loop {
+            match self.c.run()? {
+                Event::Output(value) => output.push(value),
+                Event::NeedInput | Event::Halted => return Ok(decode(&output)),
+            }
+        }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.c.is_finished()
+    }
+}
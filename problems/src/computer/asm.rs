@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use failure::{format_err, Error};
+
+use super::op::Mode;
+use super::INSTRUCTIONS;
+
+// operand value, labels are known only after the whole source is read
+#[derive(Debug)]
+enum Value {
+    Number(isize),
+    Label(String),
+    // label with a minus sign, e.g. "rb-label"
+    NegLabel(String),
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Instr {
+        op_code: isize,
+        args: Vec<(Value, Mode)>,
+    },
+    Data(Vec<Value>),
+}
+
+fn mode_flag(mode: Mode) -> isize {
+    match mode {
+        Mode::Indirect => 0,
+        Mode::Direct => 1,
+        Mode::Relative => 2,
+    }
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_value(s: &str) -> Result<Value, Error> {
+    let s = s.trim();
+
+    if is_label(s) {
+        Ok(Value::Label(s.to_owned()))
+    } else {
+        s.parse::<isize>()
+            .map(Value::Number)
+            .map_err(|_| format_err!("Invalid value `{}`", s))
+    }
+}
+
+// "[x]" is positional, "#x" immediate, "rb+x" and "rb-x" relative
+fn parse_operand(s: &str) -> Result<(Value, Mode), Error> {
+    let s = s.trim();
+
+    if s.starts_with('[') && s.ends_with(']') {
+        Ok((parse_value(&s[1..s.len() - 1])?, Mode::Indirect))
+    } else if let Some(value) = s.strip_prefix('#') {
+        Ok((parse_value(value)?, Mode::Direct))
+    } else if let Some(value) = s.strip_prefix("rb+") {
+        Ok((parse_value(value)?, Mode::Relative))
+    } else if let Some(value) = s.strip_prefix("rb-") {
+        let value = value.trim();
+
+        // the sign is parsed along with the number, -isize::MIN doesn't fit
+        if is_label(value) {
+            Ok((Value::NegLabel(value.to_owned()), Mode::Relative))
+        } else {
+            Ok((parse_value(&format!("-{}", value))?, Mode::Relative))
+        }
+    } else {
+        Err(format_err!("Invalid operand `{}`", s))
+    }
+}
+
+fn split_list(s: &str) -> Vec<&str> {
+    s.split(',').map(str::trim).filter(|s| !s.is_empty()).collect()
+}
+
+fn parse_stmt(s: &str) -> Result<Stmt, Error> {
+    let (name, rest) = match s.find(char::is_whitespace) {
+        Some(pos) => (&s[..pos], &s[pos..]),
+        None => (s, ""),
+    };
+    let name = name.to_uppercase();
+
+    if name == "DATA" {
+        let values = split_list(rest)
+            .into_iter()
+            .map(parse_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if values.is_empty() {
+            return Err(format_err!("DATA requires at least one value"));
+        }
+
+        return Ok(Stmt::Data(values));
+    }
+
+    let (op_code, _, args_num) = INSTRUCTIONS
+        .iter()
+        .find(|(_, mnemonic, _)| *mnemonic == name)
+        .ok_or_else(|| format_err!("Unknown mnemonic `{}`", name))?;
+
+    let args = split_list(rest)
+        .into_iter()
+        .map(parse_operand)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if args.len() != *args_num {
+        return Err(format_err!(
+            "{} takes {} operands, got {}",
+            name,
+            args_num,
+            args.len()
+        ));
+    }
+
+    Ok(Stmt::Instr {
+        op_code: *op_code,
+        args,
+    })
+}
+
+fn resolve(value: &Value, labels: &HashMap<String, usize>) -> Result<isize, Error> {
+    let lookup = |label: &String| {
+        labels
+            .get(label)
+            .map(|addr| *addr as isize)
+            .ok_or_else(|| format_err!("Unknown label `{}`", label))
+    };
+
+    match value {
+        Value::Number(n) => Ok(*n),
+        Value::Label(label) => lookup(label),
+        Value::NegLabel(label) => Ok(-lookup(label)?),
+    }
+}
+
+// Assembles a program written one statement per line:
+//
+//     start: INP [x]          ; labels end with a colon, comments start with a semicolon
+//            JMPF [x], #start
+//            BRK
+//     x:     DATA 0
+//
+// Numeric labels like "0004:" written by the disassembler are checked against
+// the actual address, so its output assembles back into the same program.
+pub(crate) fn assemble(source: &str) -> Result<Vec<isize>, Error> {
+    let mut labels = HashMap::new();
+    let mut stmts = Vec::new();
+    let mut addr = 0;
+
+    for (line_no, line) in source.lines().enumerate() {
+        let with_line = |err: Error| format_err!("line {}: {}", line_no + 1, err);
+
+        let mut line = line.split(';').next().unwrap_or("").trim();
+
+        while let Some(pos) = line.find(':') {
+            let label = line[..pos].trim();
+
+            if let Ok(expected) = label.parse::<usize>() {
+                if expected != addr {
+                    return Err(with_line(format_err!(
+                        "address {} doesn't match actual address {}",
+                        expected,
+                        addr
+                    )));
+                }
+            } else if !is_label(label) {
+                return Err(with_line(format_err!("Invalid label `{}`", label)));
+            } else if labels.insert(label.to_owned(), addr).is_some() {
+                return Err(with_line(format_err!("Duplicate label `{}`", label)));
+            }
+
+            line = line[pos + 1..].trim();
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let stmt = parse_stmt(line).map_err(with_line)?;
+        addr += match &stmt {
+            Stmt::Instr { args, .. } => args.len() + 1,
+            Stmt::Data(values) => values.len(),
+        };
+        stmts.push((line_no, stmt));
+    }
+
+    let mut program = Vec::with_capacity(addr);
+
+    for (line_no, stmt) in stmts {
+        let with_line = |err: Error| format_err!("line {}: {}", line_no + 1, err);
+
+        match stmt {
+            Stmt::Instr { op_code, args } => {
+                let mut code = op_code;
+                let mut mult = 100;
+
+                for (_, mode) in &args {
+                    code += mode_flag(*mode) * mult;
+                    mult *= 10;
+                }
+
+                program.push(code);
+                for (value, _) in &args {
+                    program.push(resolve(value, &labels).map_err(with_line)?);
+                }
+            }
+
+            Stmt::Data(values) => {
+                for value in &values {
+                    program.push(resolve(value, &labels).map_err(with_line)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::assemble;
+    use crate::computer::{disassemble, parse_intcode};
+
+    const PROGRAMS: [&str; 7] = [
+        include_str!("../problem2/input"),
+        include_str!("../problem5/input"),
+        include_str!("../problem7/input"),
+        include_str!("../problem9/input"),
+        include_str!("../problem11/input"),
+        include_str!("../problem13/input"),
+        include_str!("../problem15/input"),
+    ];
+
+    fn error(source: &str) -> String {
+        assemble(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn disassembly_assembles_back() {
+        for input in PROGRAMS.iter() {
+            let program = parse_intcode(input).unwrap();
+            let source: Vec<String> = disassemble(&program)
+                .iter()
+                .map(|line| line.to_string())
+                .collect();
+
+            assert_eq!(assemble(&source.join("\n")).unwrap(), program);
+        }
+    }
+
+    #[test]
+    fn labels_and_modes() {
+        let source = "
+            start: INP [x]          ; read
+                   JMPF [x], #end
+                   PUT rb-1
+                   JMPT #1, #start
+            end:   BRK
+            x:     DATA 0, -5
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![3, 11, 1006, 11, 10, 204, -1, 1105, 1, 0, 99, 0, -5]
+        );
+    }
+
+    #[test]
+    fn wrong_operand_count() {
+        assert_eq!(error("ADD [1], [2]"), "line 1: ADD takes 3 operands, got 2");
+        assert_eq!(error("BRK #1"), "line 1: BRK takes 0 operands, got 1");
+    }
+
+    #[test]
+    fn duplicate_label() {
+        assert_eq!(error("a: BRK\na: BRK"), "line 2: Duplicate label `a`");
+    }
+
+    #[test]
+    fn unknown_label() {
+        assert_eq!(error("JMPT #1, #nowhere"), "line 1: Unknown label `nowhere`");
+    }
+
+    #[test]
+    fn unknown_mnemonic() {
+        assert_eq!(error("NOP"), "line 1: Unknown mnemonic `NOP`");
+    }
+
+    #[test]
+    fn misplaced_address() {
+        assert_eq!(
+            error("0000: BRK\n0002: BRK"),
+            "line 2: address 2 doesn't match actual address 1"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use super::limits::Limits;
+use super::profile::SharedProfile;
+use super::trace::SharedSink;
+
+// Everything a machine is created with apart from its program and input: where it
+// is traced and profiled and how long it may run
+#[derive(Clone, Default)]
+pub(crate) struct Config {
+    pub(super) trace: Option<SharedSink>,
+    pub(super) profile: Option<SharedProfile>,
+    pub(super) limits: Limits,
+    // traced machines created with the config so far, they are numbered by it
+    machines: Arc<AtomicUsize>,
+}
+
+impl Config {
+    pub(crate) fn new() -> Self {
+        Config::default()
+    }
+
+    pub(crate) fn trace(mut self, sink: SharedSink) -> Self {
+        self.trace = Some(sink);
+        self
+    }
+
+    pub(crate) fn profile(mut self, profile: SharedProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub(crate) fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub(super) fn next_machine(&self) -> usize {
+        self.machines.fetch_add(1, Ordering::SeqCst) + 1
+    }
+}
+
+thread_local! {
+    static INSTALLED: RefCell<Config> = RefCell::new(Config::default());
+}
+
+// Every machine created on the current thread from now on gets the config, so
+// solutions don't need to know they are traced, profiled or limited. Machines
+// created with `Computer::with_config` take theirs explicitly.
+pub(crate) fn install(config: Config) {
+    INSTALLED.with(|installed| *installed.borrow_mut() = config);
+}
+
+pub(super) fn installed() -> Config {
+    INSTALLED.with(|installed| installed.borrow().clone())
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+use super::op::Mode;
+use super::{Computer, INSTRUCTIONS};
+
+// one line of a disassembled program
+#[derive(Debug, PartialEq)]
+pub(crate) enum Line {
+    Instr {
+        addr: usize,
+        mnemonic: &'static str,
+        // raw operand values as they are stored in memory
+        args: Vec<(isize, Mode)>,
+    },
+    // anything which doesn't decode into a valid instruction
+    Data { addr: usize, value: isize },
+}
+
+impl Line {
+    // number of memory cells taken by the line
+    pub(crate) fn cells(&self) -> usize {
+        match self {
+            Line::Instr { args, .. } => args.len() + 1,
+            Line::Data { .. } => 1,
+        }
+    }
+}
+
+fn fmt_arg(value: isize, mode: Mode) -> String {
+    match mode {
+        Mode::Indirect => format!("[{}]", value),
+        Mode::Direct => format!("#{}", value),
+        Mode::Relative if value < 0 => format!("rb-{}", value.unsigned_abs()),
+        Mode::Relative => format!("rb+{}", value),
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Instr {
+                addr,
+                mnemonic,
+                args,
+            } => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|(value, mode)| fmt_arg(*value, *mode))
+                    .collect();
+
+                if args.is_empty() {
+                    write!(f, "{:04}: {}", addr, mnemonic)
+                } else {
+                    write!(f, "{:04}: {} {}", addr, mnemonic, args.join(", "))
+                }
+            }
+
+            Line::Data { addr, value } => write!(f, "{:04}: DATA {}", addr, value),
+        }
+    }
+}
+
+// Modes::new treats every unknown flag as relative, so flags are checked here
+// to tell real instructions from data
+fn valid_modes(cell: isize, args_num: usize) -> bool {
+    let mut modes = cell / 100;
+
+    for _ in 0..args_num {
+        if modes % 10 > 2 {
+            return false;
+        }
+        modes /= 10;
+    }
+
+    modes == 0
+}
+
+fn decode(program: &[isize], addr: usize) -> Option<Line> {
+    let cell = *program.get(addr)?;
+    if cell < 0 {
+        return None;
+    }
+
+    let op = Computer::parse_op(cell);
+    let (_, mnemonic, args_num) = INSTRUCTIONS.iter().find(|(code, _, _)| *code == op.op_code)?;
+
+    // instruction cut off by the end of the program
+    if !valid_modes(cell, *args_num) || addr + args_num >= program.len() {
+        return None;
+    }
+
+    let args = (0..*args_num)
+        .map(|idx| (program[addr + idx + 1], op.mode_flags.get_mode(idx)))
+        .collect();
+
+    Some(Line::Instr {
+        addr,
+        mnemonic,
+        args,
+    })
+}
+
+// decodes a single line at the given address of a running program
+pub(crate) fn decode_at(program: &[isize], addr: usize) -> Line {
+    decode(program, addr).unwrap_or(Line::Data {
+        addr,
+        // memory beyond the end of the program reads as zeros
+        value: program.get(addr).copied().unwrap_or(0),
+    })
+}
+
+// Decodes program from the beginning to the end. Intcode mixes code and data freely,
+// so cells which don't form a valid instruction are shown as data and decoding
+// continues right after them.
+pub(crate) fn disassemble(program: &[isize]) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut addr = 0;
+
+    while addr < program.len() {
+        let line = decode_at(program, addr);
+
+        addr += line.cells();
+        lines.push(line);
+    }
+
+    lines
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+
+use failure::Fail;
+
+// everything that can go wrong while running an Intcode program
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum IntcodeError {
+    // there is no instruction with such an opcode
+    UnknownOpcode { ip: usize, opcode: isize },
+    // output was requested, but the program has finished without producing it
+    OutputExhausted { ip: usize },
+    // program asks for input, but none was given
+    InputExhausted { ip: usize },
+    // instruction writes into an operand given in immediate mode
+    DirectAddress { ip: usize, opcode: isize },
+    // instruction accesses a cell before the beginning of memory
+    NegativeAddress { ip: usize, opcode: isize, addr: isize },
+    // machine has executed as many instructions as it was allowed to
+    BudgetExceeded { ip: usize, budget: u64 },
+    // machine got into a state it has already been in without any I/O since then
+    InfiniteLoop { ip: usize },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntcodeError::UnknownOpcode { ip, opcode } => {
+                write!(f, "unknown opcode {} at {}", opcode, ip)
+            }
+            IntcodeError::OutputExhausted { ip } => write!(f, "output exhausted at {}", ip),
+            IntcodeError::InputExhausted { ip } => write!(f, "input exhausted at {}", ip),
+            IntcodeError::DirectAddress { ip, opcode } => write!(
+                f,
+                "address can't be in direct mode, opcode {} at {}",
+                opcode, ip
+            ),
+            IntcodeError::NegativeAddress { ip, opcode, addr } => write!(
+                f,
+                "negative address {}, opcode {} at {}",
+                addr, opcode, ip
+            ),
+            IntcodeError::BudgetExceeded { ip, budget } => write!(
+                f,
+                "budget of {} instructions exceeded at {}",
+                budget, ip
+            ),
+            IntcodeError::InfiniteLoop { ip } => write!(f, "infinite loop at {}", ip),
+        }
+    }
+}
+
+impl Fail for IntcodeError {}
@@ -0,0 +1,153 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Write},
+    sync::{Arc, Mutex},
+};
+
+use crossbeam::channel::{Receiver, Sender};
+
+// Where a machine takes its input from. None means no input is available yet,
+// the machine stops and waits until the next step.
+pub(crate) trait InputSource: Send {
+    fn next_input(&mut self) -> Option<isize>;
+}
+
+// Where a machine puts its output, a machine with a sink attached doesn't
+// stop on every output value.
+pub(crate) trait OutputSink: Send {
+    fn put(&mut self, value: isize);
+}
+
+// attached I/O can't be forked, so a cloned machine starts without it
+pub(super) struct Port<T: ?Sized>(pub(super) Option<Box<T>>);
+
+impl<T: ?Sized> Clone for Port<T> {
+    fn clone(&self) -> Self {
+        Port(None)
+    }
+}
+
+impl InputSource for VecDeque<isize> {
+    fn next_input(&mut self) -> Option<isize> {
+        self.pop_front()
+    }
+}
+
+impl OutputSink for VecDeque<isize> {
+    fn put(&mut self, value: isize) {
+        self.push_back(value);
+    }
+}
+
+impl OutputSink for Vec<isize> {
+    fn put(&mut self, value: isize) {
+        self.push(value);
+    }
+}
+
+impl<F: FnMut() -> Option<isize> + Send> InputSource for F {
+    fn next_input(&mut self) -> Option<isize> {
+        self()
+    }
+}
+
+// channels block until the other side sends a value or hangs up
+impl InputSource for Receiver<isize> {
+    fn next_input(&mut self) -> Option<isize> {
+        self.recv().ok()
+    }
+}
+
+impl OutputSink for Sender<isize> {
+    fn put(&mut self, value: isize) {
+        // nobody is interested in the output anymore
+        self.send(value).ok();
+    }
+}
+
+// Queue shared between a machine and its owner, the owner pushes input or
+// drains output between steps
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Queue(Arc<Mutex<VecDeque<isize>>>);
+
+impl Queue {
+    pub(crate) fn new() -> Self {
+        Queue::default()
+    }
+
+    pub(crate) fn push(&self, value: isize) {
+        self.0.lock().unwrap().push_back(value);
+    }
+
+    pub(crate) fn drain(&self) -> Vec<isize> {
+        self.0.lock().unwrap().drain(..).collect()
+    }
+}
+
+impl InputSource for Queue {
+    fn next_input(&mut self) -> Option<isize> {
+        self.0.lock().unwrap().pop_front()
+    }
+}
+
+impl OutputSink for Queue {
+    fn put(&mut self, value: isize) {
+        self.push(value);
+    }
+}
+
+// reads values separated by commas or whitespace, e.g. from a file,
+// input ends at the end of the reader or at the first value which isn't a number
+pub(crate) struct ReaderInput<R: BufRead + Send> {
+    reader: R,
+    pending: VecDeque<isize>,
+    finished: bool,
+}
+
+impl<R: BufRead + Send> ReaderInput<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        ReaderInput {
+            reader,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<R: BufRead + Send> InputSource for ReaderInput<R> {
+    fn next_input(&mut self) -> Option<isize> {
+        while self.pending.is_empty() && !self.finished {
+            let mut line = String::new();
+
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => self.finished = true,
+                Ok(_) => {
+                    for token in line.split(|c: char| c == ',' || c.is_whitespace()) {
+                        if token.is_empty() {
+                            continue;
+                        }
+                        match token.parse::<isize>() {
+                            Ok(value) => self.pending.push_back(value),
+                            Err(_) => {
+                                self.finished = true;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+// writes every value on its own line, e.g. into a file
+pub(crate) struct WriterOutput<W: Write + Send>(pub(crate) W);
+
+impl<W: Write + Send> OutputSink for WriterOutput<W> {
+    fn put(&mut self, value: isize) {
+        // output is best effort, a broken writer must not stop the program
+        writeln!(self.0, "{}", value).ok();
+    }
+}
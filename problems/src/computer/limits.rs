@@ -0,0 +1,10 @@
+// Safety nets for programs which may never stop on their own
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub(crate) struct Limits {
+    // number of instructions a machine may execute during its whole life
+    pub budget: Option<u64>,
+    // fail as soon as the machine gets into a state it has already been in
+    // without any input or output in between, costs hashing of the whole
+    // memory on every instruction
+    pub detect_loops: bool,
+}
@@ -1,13 +1,43 @@
+mod ascii;
+mod asm;
+mod config;
+mod disasm;
+mod error;
 mod helpers;
+mod io;
+mod limits;
+mod network;
 mod op;
-
-use std::collections::{HashMap, VecDeque};
-
-use failure::{format_err, Error};
+mod pipeline;
+mod profile;
+mod snapshot;
+mod trace;
+
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use disasm::Line;
+use io::Port;
 use op::{Arg, Mode, Modes, Op, Operands};
-use utils::{split_digits, ParseResult};
-
-pub(crate) use helpers::{consume_until_break, parse_intcode, stop_or_input};
+use profile::SharedProfile;
+use trace::SharedSink as Sink;
+use utils::split_digits;
+
+pub(crate) use ascii::{Ascii, Chunk};
+pub(crate) use asm::assemble;
+pub(crate) use config::{install as install_config, Config};
+pub(crate) use disasm::{decode_at, disassemble};
+pub(crate) use error::IntcodeError;
+pub(crate) use helpers::{consume_until_break, parse_intcode};
+pub(crate) use io::{InputSource, OutputSink, Queue, ReaderInput, WriterOutput};
+pub(crate) use limits::Limits;
+pub(crate) use network::{Network, State as NetworkState};
+pub(crate) use pipeline::Pipeline;
+pub(crate) use profile::Profile;
+pub(crate) use snapshot::Snapshot;
+pub(crate) use trace::{RingBuffer, SharedSink, TraceEvent, TraceSink, WriterSink};
 
 const ADD: isize = 1;
 const MUL: isize = 2;
@@ -20,61 +50,287 @@ const EQ: isize = 8;
 const BASE: isize = 9;
 const BRK: isize = 99;
 
-const WAIT_INPUT: usize = 1;
-const WAIT_OUTPUT: usize = 2;
-const FINISH: usize = 0;
+// mnemonic and number of operands of every instruction
+const INSTRUCTIONS: [(isize, &str, usize); 10] = [
+    (ADD, "ADD", 3),
+    (MUL, "MUL", 3),
+    (INP, "INP", 1),
+    (PUT, "PUT", 1),
+    (JMPT, "JMPT", 2),
+    (JMPF, "JMPF", 2),
+    (LT, "LT", 3),
+    (EQ, "EQ", 3),
+    (BASE, "BASE", 1),
+    (BRK, "BRK", 0),
+];
+
+// Memory grows contiguously only up to this address, cells written past it are
+// kept apart, so a single write to a far address doesn't allocate everything before it
+const CONTIGUOUS_MEMORY: usize = 1 << 20;
+
+// reason the machine stopped running
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Event {
+    // program waits for a value, either from the input source or set_stdin
+    NeedInput,
+    // program produced a value and there is no output sink to take it
+    Output(isize),
+    Halted,
+}
 
+// cloning forks the machine, both copies run independently from then on
+#[derive(Clone)]
 pub(crate) struct Computer {
     pub stdout: Option<isize>,
     pub stdin: isize,
 
     init_input: Option<VecDeque<isize>>,
+    // consulted once the initial input is exhausted
+    input_source: Port<dyn InputSource>,
+    // without a sink every output value stops the machine until it's taken
+    output_sink: Port<dyn OutputSink>,
 
     finished: bool,
     wait_input: bool,
     input_dest: usize,
 
-    program: HashMap<usize, isize>,
+    // memory grows on writes past its end, cells beyond it read as zeros
+    program: Vec<isize>,
+    // cells past CONTIGUOUS_MEMORY which have been written to
+    far_memory: BTreeMap<usize, isize>,
     offset: isize,
     ip: usize,
+
+    // sink along with the number of this machine, None unless it is traced
+    tracer: Option<(Sink, usize)>,
+    // memory writes of the instruction being traced or profiled
+    trace_writes: Vec<(usize, isize)>,
+    // None unless it is profiled
+    profile: Option<SharedProfile>,
+
+    limits: Limits,
+    // instructions executed since the machine was created
+    executed: u64,
+    // hashes of states since the last input or output, used to detect loops
+    seen_states: HashSet<u64>,
 }
 
 impl Computer {
+    // the machine gets the config installed on the current thread
     pub(crate) fn new(input_program: &[isize], init_input: Option<Vec<isize>>) -> Self {
-        let mut program: HashMap<usize, isize> = HashMap::new();
+        Self::with_config(input_program, init_input, &config::installed())
+    }
 
-        let tmp = match init_input {
-            Some(xs) => Some(VecDeque::from(xs)),
-            None => None,
-        };
+    pub(crate) fn with_config(
+        input_program: &[isize],
+        init_input: Option<Vec<isize>>,
+        config: &Config,
+    ) -> Self {
+        let program = input_program.to_vec();
 
-        // copy program
-        for (idx, item) in input_program.iter().enumerate() {
-            program.insert(idx, *item);
-        }
+        let tmp = init_input.map(VecDeque::from);
 
         Self {
             stdout: None,
             stdin: 0,
 
             init_input: tmp,
+            input_source: Port(None),
+            output_sink: Port(None),
 
             finished: false,
             wait_input: false,
             input_dest: 0,
 
             program,
+            far_memory: BTreeMap::new(),
             offset: 0,
             ip: 0,
+
+            tracer: config
+                .trace
+                .clone()
+                .map(|sink| (sink, config.next_machine())),
+            trace_writes: Vec::new(),
+            profile: config.profile.clone(),
+
+            limits: config.limits,
+            executed: 0,
+            seen_states: HashSet::new(),
         }
     }
 
     fn set_cell(&mut self, idx: usize, val: isize) {
-        self.program.insert(idx as usize, val);
+        if idx >= self.program.len() {
+            if idx >= CONTIGUOUS_MEMORY {
+                self.far_memory.insert(idx, val);
+            } else {
+                self.program.resize(idx + 1, 0);
+            }
+        }
+        if let Some(cell) = self.program.get_mut(idx) {
+            *cell = val;
+        }
+
+        if self.tracer.is_some() || self.profile.is_some() {
+            self.trace_writes.push((idx, val));
+        }
+    }
+
+    fn trace(&self, event: TraceEvent) {
+        if let Some((sink, _)) = &self.tracer {
+            sink.lock().unwrap().record(event);
+        }
+    }
+
+    // values operands of the instruction resolve to in the current state
+    fn operand_values(&self, line: &Line) -> Vec<isize> {
+        match line {
+            Line::Instr { args, .. } => args
+                .iter()
+                .map(|(value, mode)| match mode {
+                    Mode::Direct => *value,
+                    Mode::Indirect => self.get_cell(*value as usize),
+                    Mode::Relative => self.get_cell((self.offset + *value) as usize),
+                })
+                .collect(),
+            Line::Data { .. } => Vec::new(),
+        }
+    }
+
+    fn exec_traced(&mut self) -> Result<Option<Event>, IntcodeError> {
+        let line = decode_at(&self.program, self.ip);
+        let values = self.operand_values(&line);
+        let offset = self.offset;
+
+        self.trace_writes.clear();
+        let res = self.exec_instr();
+
+        let machine = self.tracer.as_ref().map_or(0, |(_, machine)| *machine);
+        self.trace(TraceEvent::Exec {
+            machine,
+            instr: line.to_string(),
+            values,
+            writes: self.trace_writes.clone(),
+            base: if self.offset != offset {
+                Some(self.offset)
+            } else {
+                None
+            },
+        });
+
+        res
+    }
+
+    fn exec_profiled(&mut self) -> Result<Option<Event>, IntcodeError> {
+        let ip = self.ip;
+        let opcode = self.cur_opcode();
+        let reads = profile::operand_reads(&decode_at(&self.program, ip), opcode, self.offset);
+
+        self.trace_writes.clear();
+        let res = match self.tracer {
+            Some(_) => self.exec_traced(),
+            None => self.exec_instr(),
+        };
+
+        if let Some(profile) = &self.profile {
+            profile
+                .lock()
+                .unwrap()
+                .record(ip, opcode, &reads, &self.trace_writes);
+        }
+
+        res
     }
 
     pub(crate) fn get_cell(&self, idx: usize) -> isize {
-        *self.program.get(&(idx as usize)).unwrap_or(&0)
+        match self.program.get(idx) {
+            Some(value) => *value,
+            None => *self.far_memory.get(&idx).unwrap_or(&0),
+        }
+    }
+
+    fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (&self.program, &self.far_memory, self.ip, self.offset).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // The same state repeated without any I/O in between repeats forever.
+    // Only hashes are kept, so a collision might report a loop which isn't there,
+    // but the chance is negligible.
+    fn check_loop(&mut self, io: bool, event: Option<Event>) -> Result<(), IntcodeError> {
+        if io || event.is_some() {
+            self.seen_states.clear();
+        } else if !self.seen_states.insert(self.state_hash()) {
+            return Err(IntcodeError::InfiniteLoop { ip: self.ip });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_input_source(&mut self, source: impl InputSource + 'static) {
+        self.input_source = Port(Some(Box::new(source)));
+    }
+
+    pub(crate) fn set_output_sink(&mut self, sink: impl OutputSink + 'static) {
+        self.output_sink = Port(Some(Box::new(sink)));
+    }
+
+    fn next_input(&mut self) -> Option<isize> {
+        match self.init_input.as_mut().and_then(VecDeque::pop_front) {
+            Some(value) => Some(value),
+            None => self.input_source.0.as_mut().and_then(|source| source.next_input()),
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.program.clone(),
+            far_memory: self.far_memory.clone(),
+            ip: self.ip,
+            offset: self.offset,
+
+            stdin: self.stdin,
+            stdout: self.stdout,
+            input: self.init_input.clone(),
+
+            finished: self.finished,
+            wait_input: self.wait_input,
+            input_dest: self.input_dest,
+        }
+    }
+
+    // tracing isn't a part of the state, the machine keeps its own tracer
+    pub(crate) fn restore(&mut self, snapshot: &Snapshot) {
+        self.program.clone_from(&snapshot.memory);
+        self.far_memory.clone_from(&snapshot.far_memory);
+        self.ip = snapshot.ip;
+        self.offset = snapshot.offset;
+
+        self.stdin = snapshot.stdin;
+        self.stdout = snapshot.stdout;
+        self.init_input.clone_from(&snapshot.input);
+
+        self.finished = snapshot.finished;
+        self.wait_input = snapshot.wait_input;
+        self.input_dest = snapshot.input_dest;
+
+        // states seen before are no longer on the way
+        self.seen_states.clear();
+    }
+
+    pub(crate) fn ip(&self) -> usize {
+        self.ip
+    }
+
+    // relative base
+    pub(crate) fn offset(&self) -> isize {
+        self.offset
+    }
+
+    // contiguous part of memory, far cells are only available through get_cell
+    pub(crate) fn memory(&self) -> &[isize] {
+        &self.program
     }
 
     pub(crate) fn is_finished(&self) -> bool {
@@ -85,138 +341,230 @@ impl Computer {
         self.wait_input
     }
 
-    pub(crate) fn get_output(&mut self) -> Result<isize, Error> {
-        match self.stdout {
-            Some(x) => {
-                self.stdout = None;
-                Ok(x)
+    pub(crate) fn set_stdin(&mut self, val: isize) {
+        self.stdin = val;
+    }
+
+    // runs until the program asks for input, produces output or finishes,
+    // an output value is handed over in the event and isn't kept in stdout
+    pub(crate) fn run(&mut self) -> Result<Event, IntcodeError> {
+        loop {
+            if let Some(event) = self.step_instr()? {
+                if let Event::Output(_) = event {
+                    self.stdout = None;
+                }
+                return Ok(event);
             }
+        }
+    }
 
-            None => Err(format_err!("Output exhausted")),
+    // runs until the program produces exactly n values
+    pub(crate) fn run_until_output(&mut self, n: usize) -> Result<Vec<isize>, IntcodeError> {
+        let mut output = Vec::with_capacity(n);
+
+        while output.len() < n {
+            match self.run()? {
+                Event::Output(value) => output.push(value),
+                Event::NeedInput => return Err(IntcodeError::InputExhausted { ip: self.ip }),
+                Event::Halted => return Err(IntcodeError::OutputExhausted { ip: self.ip }),
+            }
         }
+
+        Ok(output)
     }
 
-    pub(crate) fn set_stdin(&mut self, val: isize) {
-        self.stdin = val;
+    // runs the program to its end collecting everything it outputs
+    pub(crate) fn run_until_halt(&mut self) -> Result<Vec<isize>, IntcodeError> {
+        let mut output = Vec::new();
+
+        loop {
+            match self.run()? {
+                Event::Output(value) => output.push(value),
+                Event::NeedInput => return Err(IntcodeError::InputExhausted { ip: self.ip }),
+                Event::Halted => return Ok(output),
+            }
+        }
     }
 
-    pub(crate) fn step(&mut self) -> Result<usize, Error> {
+    // Executes a single instruction, returns an event if the program stopped
+    // because of input, output or its end. Output stays in stdout until taken.
+    pub(crate) fn step_instr(&mut self) -> Result<Option<Event>, IntcodeError> {
         if self.wait_input {
+            // without an input source the value is given through set_stdin
+            if self.input_source.0.is_some() {
+                match self.next_input() {
+                    Some(value) => self.stdin = value,
+                    None => return Ok(Some(Event::NeedInput)),
+                }
+            }
+
             self.set_cell(self.input_dest, self.stdin);
             self.wait_input = false;
+
+            // the instruction asking for the value has been profiled already
+            if let Some(profile) = &self.profile {
+                profile.lock().unwrap().record_write(self.input_dest);
+            }
+
+            if let Some((_, machine)) = &self.tracer {
+                self.trace(TraceEvent::Input {
+                    machine: *machine,
+                    addr: self.input_dest,
+                    value: self.stdin,
+                });
+            }
         }
 
-        loop {
-            let op = Self::parse_op(self.get_cell(self.ip));
-
-            match op.op_code {
-                ADD => {
-                    if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
-                        self.set_cell(
-                            self.get_arg_addr(to)?,
-                            self.get_arg_value(a)? + self.get_arg_value(b)?,
-                        );
-                        self.ip += 4;
-                    }
+        if let Some(budget) = self.limits.budget {
+            if self.executed >= budget {
+                return Err(IntcodeError::BudgetExceeded {
+                    ip: self.ip,
+                    budget,
+                });
+            }
+        }
+        self.executed += 1;
+
+        let io = self.limits.detect_loops && matches!(self.cur_opcode(), INP | PUT);
+
+        let event = match (&self.tracer, &self.profile) {
+            (_, Some(_)) => self.exec_profiled(),
+            (Some(_), None) => self.exec_traced(),
+            (None, None) => self.exec_instr(),
+        }?;
+
+        if self.limits.detect_loops {
+            self.check_loop(io, event)?;
+        }
+
+        Ok(event)
+    }
+
+    fn exec_instr(&mut self) -> Result<Option<Event>, IntcodeError> {
+        let op = Self::parse_op(self.get_cell(self.ip));
+
+        match op.op_code {
+            ADD => {
+                if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
+                    self.set_cell(
+                        self.get_arg_addr(to)?,
+                        self.get_arg_value(a)? + self.get_arg_value(b)?,
+                    );
+                    self.ip += 4;
                 }
+            }
 
-                MUL => {
-                    if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
-                        self.set_cell(
-                            self.get_arg_addr(to)?,
-                            self.get_arg_value(a)? * self.get_arg_value(b)?,
-                        );
-                        self.ip += 4;
-                    }
+            MUL => {
+                if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
+                    self.set_cell(
+                        self.get_arg_addr(to)?,
+                        self.get_arg_value(a)? * self.get_arg_value(b)?,
+                    );
+                    self.ip += 4;
                 }
+            }
 
-                INP => {
-                    if let Operands::One(a) = self.get_ops(self.ip, &op.mode_flags, 1)? {
-                        self.input_dest = self.get_arg_addr(a)? as usize;
-                        self.ip += 2;
-                        if self.init_input.is_some()
-                            && !self.init_input.as_ref().unwrap().is_empty()
-                        {
-                            self.stdin = self.init_input.as_mut().unwrap().pop_front().unwrap();
+            INP => {
+                if let Operands::One(a) = self.get_ops(self.ip, &op.mode_flags, 1)? {
+                    self.input_dest = self.get_arg_addr(a)?;
+                    self.ip += 2;
+                    match self.next_input() {
+                        Some(value) => {
+                            self.stdin = value;
                             self.set_cell(self.input_dest, self.stdin);
-                        } else {
+                        }
+                        None => {
                             self.wait_input = true;
-                            return Ok(WAIT_INPUT);
+                            return Ok(Some(Event::NeedInput));
                         }
                     }
                 }
+            }
 
-                PUT => {
-                    if let Operands::One(a) = self.get_ops(self.ip, &op.mode_flags, 1)? {
-                        self.stdout = Some(self.get_arg_value(a)?);
-                        self.ip += 2;
-                        return Ok(WAIT_OUTPUT);
-                    }
-                }
+            PUT => {
+                if let Operands::One(a) = self.get_ops(self.ip, &op.mode_flags, 1)? {
+                    let value = self.get_arg_value(a)?;
+                    self.ip += 2;
 
-                JMPT => {
-                    if let Operands::Two(value, to) = self.get_ops(self.ip, &op.mode_flags, 2)? {
-                        if self.get_arg_value(value)? != 0 {
-                            self.ip = self.get_arg_value(to)? as usize;
-                        } else {
-                            self.ip += 3;
+                    match self.output_sink.0.as_mut() {
+                        Some(sink) => sink.put(value),
+                        None => {
+                            self.stdout = Some(value);
+                            return Ok(Some(Event::Output(value)));
                         }
                     }
                 }
+            }
 
-                JMPF => {
-                    if let Operands::Two(value, to) = self.get_ops(self.ip, &op.mode_flags, 2)? {
-                        if self.get_arg_value(value)? == 0 {
-                            self.ip = self.get_arg_value(to)? as usize;
-                        } else {
-                            self.ip += 3;
-                        }
+            JMPT => {
+                if let Operands::Two(value, to) = self.get_ops(self.ip, &op.mode_flags, 2)? {
+                    if self.get_arg_value(value)? != 0 {
+                        self.ip = self.get_arg_value(to)? as usize;
+                    } else {
+                        self.ip += 3;
                     }
                 }
+            }
 
-                LT => {
-                    if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
-                        self.set_cell(
-                            self.get_arg_addr(to)? as usize,
-                            if self.get_arg_value(a)? < self.get_arg_value(b)? {
-                                1
-                            } else {
-                                0
-                            },
-                        );
-                        self.ip += 4;
+            JMPF => {
+                if let Operands::Two(value, to) = self.get_ops(self.ip, &op.mode_flags, 2)? {
+                    if self.get_arg_value(value)? == 0 {
+                        self.ip = self.get_arg_value(to)? as usize;
+                    } else {
+                        self.ip += 3;
                     }
                 }
+            }
 
-                EQ => {
-                    if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
-                        self.set_cell(
-                            self.get_arg_addr(to)? as usize,
-                            if self.get_arg_value(a)? == self.get_arg_value(b)? {
-                                1
-                            } else {
-                                0
-                            },
-                        );
-                        self.ip += 4;
-                    }
+            LT => {
+                if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
+                    self.set_cell(
+                        self.get_arg_addr(to)?,
+                        if self.get_arg_value(a)? < self.get_arg_value(b)? {
+                            1
+                        } else {
+                            0
+                        },
+                    );
+                    self.ip += 4;
                 }
+            }
 
-                BASE => {
-                    if let Operands::One(a) = self.get_ops(self.ip, &op.mode_flags, 1)? {
-                        self.offset += self.get_arg_value(a)?;
-                        self.ip += 2;
-                    }
+            EQ => {
+                if let Operands::Three(a, b, to) = self.get_ops(self.ip, &op.mode_flags, 3)? {
+                    self.set_cell(
+                        self.get_arg_addr(to)?,
+                        if self.get_arg_value(a)? == self.get_arg_value(b)? {
+                            1
+                        } else {
+                            0
+                        },
+                    );
+                    self.ip += 4;
                 }
+            }
 
-                BRK => {
-                    self.finished = true;
-                    return Ok(FINISH);
+            BASE => {
+                if let Operands::One(a) = self.get_ops(self.ip, &op.mode_flags, 1)? {
+                    self.offset += self.get_arg_value(a)?;
+                    self.ip += 2;
                 }
+            }
+
+            BRK => {
+                self.finished = true;
+                return Ok(Some(Event::Halted));
+            }
 
-                s => return Err(format_err!("Unknown state {}", s)),
+            opcode => {
+                return Err(IntcodeError::UnknownOpcode {
+                    ip: self.ip,
+                    opcode,
+                })
             }
         }
+
+        Ok(None)
     }
 
     fn parse_op(op: isize) -> Op {
@@ -232,16 +580,30 @@ impl Computer {
         }
     }
 
-    fn get_arg_value(&self, arg: Arg) -> ParseResult<isize> {
+    fn get_arg_value(&self, arg: Arg) -> Result<isize, IntcodeError> {
         Ok(match arg.mode {
             Mode::Direct => arg.value,
             _ => self.get_cell(arg.value as usize),
         })
     }
 
-    fn get_arg_addr(&self, arg: Arg) -> ParseResult<usize> {
+    // instructions are executed before ip moves, so it still points to the opcode
+    fn cur_opcode(&self) -> isize {
+        self.get_cell(self.ip) % 100
+    }
+
+    fn get_arg_addr(&self, arg: Arg) -> Result<usize, IntcodeError> {
         match arg.mode {
-            Mode::Direct => Err(format_err!("Address can't be in direct mode")),
+            Mode::Direct => Err(IntcodeError::DirectAddress {
+                ip: self.ip,
+                opcode: self.cur_opcode(),
+            }),
+            // would grow memory to the whole address space otherwise
+            _ if arg.value < 0 => Err(IntcodeError::NegativeAddress {
+                ip: self.ip,
+                opcode: self.cur_opcode(),
+                addr: arg.value,
+            }),
             _ => Ok(arg.value as usize),
         }
     }
@@ -257,7 +619,12 @@ impl Computer {
         }
     }
 
-    fn get_ops(&self, op_idx: usize, mode_flags: &Modes, args_num: isize) -> ParseResult<Operands> {
+    fn get_ops(
+        &self,
+        op_idx: usize,
+        mode_flags: &Modes,
+        args_num: isize,
+    ) -> Result<Operands, IntcodeError> {
         match args_num {
             1 => Ok(Operands::One(
                 self.get_operand(self.get_cell(op_idx + 1), mode_flags.get_mode(0)),
@@ -274,7 +641,8 @@ impl Computer {
                 self.get_operand(self.get_cell(op_idx + 3), mode_flags.get_mode(2)),
             )),
 
-            n => Err(format_err!("Wrong number of arguments {}", n)),
+            // instructions are called with constant numbers of operands
+            n => unreachable!("wrong number of arguments {}", n),
         }
     }
 }
@@ -0,0 +1,259 @@
+use std::{collections::VecDeque, fmt};
+
+use failure::{format_err, Error};
+
+use super::{Computer, Event};
+
+// packets sent to this address are kept by the NAT instead of being delivered
+pub(crate) const NAT: usize = 255;
+
+// machines read this value when no packet is waiting for them
+const NO_PACKET: isize = -1;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Packet {
+    pub from: usize,
+    pub to: usize,
+    pub x: isize,
+    pub y: isize,
+}
+
+fn fmt_addr(addr: usize) -> String {
+    if addr == NAT {
+        "NAT".to_owned()
+    } else {
+        addr.to_string()
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>3} -> {:<3}  x: {}, y: {}",
+            fmt_addr(self.from),
+            fmt_addr(self.to),
+            self.x,
+            self.y
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum State {
+    // some packets are still on their way
+    Busy,
+    // all queues are empty and nobody has sent anything during the last round
+    Idle,
+    Halted,
+}
+
+struct Node {
+    c: Computer,
+    // x and y of the packets waiting to be read
+    incoming: VecDeque<isize>,
+    // values of a packet which is being sent
+    outgoing: Vec<isize>,
+    halted: bool,
+}
+
+// Machines running the same program, every one of them is given its address as
+// the first input and then exchanges packets of three values: address, x and y.
+pub(crate) struct Network {
+    nodes: Vec<Node>,
+    // the last packet sent to the NAT
+    nat: Option<Packet>,
+}
+
+impl Network {
+    pub(crate) fn new(program: &[isize], size: usize) -> Self {
+        let nodes = (0..size)
+            .map(|addr| Node {
+                c: Computer::new(program, Some(vec![addr as isize])),
+                incoming: VecDeque::new(),
+                outgoing: Vec::with_capacity(3),
+                halted: false,
+            })
+            .collect();
+
+        Network { nodes, nat: None }
+    }
+
+    fn deliver(&mut self, packet: Packet) -> Result<(), Error> {
+        if packet.to == NAT {
+            self.nat = Some(packet);
+            return Ok(());
+        }
+
+        match self.nodes.get_mut(packet.to) {
+            Some(node) => {
+                node.incoming.extend(&[packet.x, packet.y]);
+                Ok(())
+            }
+            None => Err(format_err!(
+                "Machine {} sent a packet to unknown address {}",
+                packet.from,
+                packet.to
+            )),
+        }
+    }
+
+    // Runs every machine in turn until it asks for a packet which isn't there.
+    // Packets are delivered once their sender's turn is over, all packets sent
+    // during the round are returned in the order they were sent.
+    pub(crate) fn round(&mut self) -> Result<(Vec<Packet>, State), Error> {
+        let mut sent = Vec::new();
+
+        for from in 0..self.nodes.len() {
+            let mut packets = Vec::new();
+            let node = &mut self.nodes[from];
+
+            while !node.halted {
+                match node.c.run()? {
+                    Event::Output(value) => {
+                        node.outgoing.push(value);
+
+                        if let [to, x, y] = node.outgoing[..] {
+                            packets.push(Packet {
+                                from,
+                                to: to as usize,
+                                x,
+                                y,
+                            });
+                            node.outgoing.clear();
+                        }
+                    }
+
+                    Event::NeedInput => match node.incoming.pop_front() {
+                        Some(value) => node.c.set_stdin(value),
+                        None => {
+                            node.c.set_stdin(NO_PACKET);
+                            break;
+                        }
+                    },
+
+                    Event::Halted => node.halted = true,
+                }
+            }
+
+            for packet in packets {
+                self.deliver(packet)?;
+                sent.push(packet);
+            }
+        }
+
+        let state = if self.nodes.iter().all(|node| node.halted) {
+            State::Halted
+        } else if sent.is_empty() && self.nodes.iter().all(|node| node.incoming.is_empty()) {
+            State::Idle
+        } else {
+            State::Busy
+        };
+
+        Ok((sent, state))
+    }
+
+    // NAT resends the last packet it got to machine 0, returns None if it has
+    // got nothing so far
+    pub(crate) fn wake(&mut self) -> Result<Option<Packet>, Error> {
+        match self.nat {
+            Some(packet) => {
+                let packet = Packet {
+                    from: NAT,
+                    to: 0,
+                    ..packet
+                };
+                self.deliver(packet)?;
+                Ok(Some(packet))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Network, Packet, State, NAT};
+    use crate::computer::assemble;
+
+    // machine 0 sends a packet to machine 1, everyone forwards what they get to the NAT
+    const FORWARD: &str = "
+               INP [addr]
+               JMPT [addr], #loop
+               PUT #1
+               PUT #10
+               PUT #20
+        loop:  INP [x]
+               EQ [x], #-1, [t]
+               JMPT [t], #loop
+               INP [y]
+               PUT #255
+               PUT [x]
+               PUT [y]
+               JMPT #1, #loop
+        addr:  DATA 0
+        x:     DATA 0
+        y:     DATA 0
+        t:     DATA 0
+    ";
+
+    fn network(source: &str, size: usize) -> Network {
+        Network::new(&assemble(source).unwrap(), size)
+    }
+
+    fn packet(from: usize, to: usize, x: isize, y: isize) -> Packet {
+        Packet { from, to, x, y }
+    }
+
+    #[test]
+    fn packets_are_routed() {
+        let mut net = network(FORWARD, 3);
+
+        assert_eq!(
+            net.round().unwrap(),
+            (
+                vec![packet(0, 1, 10, 20), packet(1, NAT, 10, 20)],
+                State::Busy
+            )
+        );
+    }
+
+    #[test]
+    fn network_goes_idle() {
+        let mut net = network(FORWARD, 3);
+
+        net.round().unwrap();
+        assert_eq!(net.round().unwrap(), (Vec::new(), State::Idle));
+        assert_eq!(net.round().unwrap(), (Vec::new(), State::Idle));
+    }
+
+    #[test]
+    fn nat_wakes_machine_zero() {
+        let mut net = network(FORWARD, 3);
+        assert_eq!(net.wake().unwrap(), None);
+
+        net.round().unwrap();
+        net.round().unwrap();
+
+        assert_eq!(net.wake().unwrap(), Some(packet(NAT, 0, 10, 20)));
+        assert_eq!(
+            net.round().unwrap(),
+            (vec![packet(0, NAT, 10, 20)], State::Busy)
+        );
+    }
+
+    #[test]
+    fn halted_network() {
+        let mut net = network("BRK", 2);
+        assert_eq!(net.round().unwrap(), (Vec::new(), State::Halted));
+    }
+
+    #[test]
+    fn unknown_address() {
+        let mut net = network("INP [0]\nPUT #7\nPUT #1\nPUT #2\nBRK", 2);
+        assert_eq!(
+            net.round().unwrap_err().to_string(),
+            "Machine 0 sent a packet to unknown address 7"
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crossbeam::{
+    channel::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+};
+use failure::{format_err, Error};
+
+use super::{Computer, Event, InputSource, OutputSink, Queue};
+
+// how often a machine waiting for input checks whether the whole pipeline is stuck
+const POLL: Duration = Duration::from_millis(10);
+
+// What the machines of a pipeline are doing, changed only under the lock, so the
+// counts always describe the pipeline at a single moment
+#[derive(Debug, Default)]
+struct Activity {
+    // machines which haven't stopped yet
+    running: usize,
+    // machines blocked on their empty input
+    waiting: usize,
+    // values sent and not taken by their machines yet
+    in_flight: usize,
+    // some machine has halted, the pipeline is shutting down
+    halted: bool,
+}
+
+impl Activity {
+    // nobody can send anything anymore, e.g. in a loop which hasn't been fed
+    fn is_stuck(&self) -> bool {
+        self.in_flight == 0 && self.waiting == self.running
+    }
+}
+
+type SharedActivity = Arc<Mutex<Activity>>;
+
+// passes every output value to all connected machines and keeps a copy of it
+struct Tap {
+    senders: Vec<Sender<isize>>,
+    log: Queue,
+    activity: SharedActivity,
+}
+
+impl OutputSink for Tap {
+    fn put(&mut self, value: isize) {
+        let mut activity = self.activity.lock().unwrap();
+        for sender in &self.senders {
+            // a machine which has stopped isn't interested in the value anymore
+            if sender.send(value).is_ok() {
+                activity.in_flight += 1;
+            }
+        }
+        self.log.push(value);
+    }
+}
+
+// Input of a machine, ends once nothing can come through it anymore: all machines
+// writing into it have stopped or the whole pipeline is stuck
+struct Inbox {
+    receiver: Receiver<isize>,
+    activity: SharedActivity,
+}
+
+impl InputSource for Inbox {
+    fn next_input(&mut self) -> Option<isize> {
+        self.activity.lock().unwrap().waiting += 1;
+
+        let value = loop {
+            match self.receiver.recv_timeout(POLL) {
+                Ok(value) => break Some(value),
+                Err(RecvTimeoutError::Disconnected) => break None,
+                Err(RecvTimeoutError::Timeout) => {
+                    if self.activity.lock().unwrap().is_stuck() {
+                        break None;
+                    }
+                }
+            }
+        };
+
+        let mut activity = self.activity.lock().unwrap();
+        activity.waiting -= 1;
+        if value.is_some() {
+            activity.in_flight -= 1;
+        }
+        value
+    }
+}
+
+struct Stage {
+    c: Computer,
+    input: (Sender<isize>, Receiver<isize>),
+    outputs: Vec<Sender<isize>>,
+}
+
+// Machines running on their own threads with outputs connected to inputs by
+// channels. Any topology can be built: chains, loops or several machines reading
+// output of a single one. A machine which halts closes its outputs and shuts the
+// pipeline down: machines left without input afterwards simply stop. If nothing
+// can move while no machine has halted, e.g. in a loop which hasn't been fed, the
+// run fails instead of blocking forever.
+#[derive(Default)]
+pub(crate) struct Pipeline {
+    stages: Vec<Stage>,
+    activity: SharedActivity,
+}
+
+impl Pipeline {
+    pub(crate) fn new() -> Self {
+        Pipeline::default()
+    }
+
+    // returns the index of the machine used to connect it
+    pub(crate) fn add(&mut self, c: Computer) -> usize {
+        self.stages.push(Stage {
+            c,
+            input: channel::unbounded(),
+            outputs: Vec::new(),
+        });
+        self.stages.len() - 1
+    }
+
+    pub(crate) fn connect(&mut self, from: usize, to: usize) -> &mut Self {
+        let sender = self.stages[to].input.0.clone();
+        self.stages[from].outputs.push(sender);
+        self
+    }
+
+    // queues a value for the machine to read once its initial input is over
+    pub(crate) fn feed(&mut self, to: usize, value: isize) -> &mut Self {
+        let (sender, _) = &self.stages[to].input;
+        // the receiver is kept alongside, so the channel can't be closed
+        sender.send(value).unwrap();
+        self.activity.lock().unwrap().in_flight += 1;
+        self
+    }
+
+    // Runs all machines until every one of them stops, returns everything each
+    // of them has output
+    pub(crate) fn run(self) -> Result<Vec<Vec<isize>>, Error> {
+        let Pipeline { stages, activity } = self;
+        let logs: Vec<Queue> = stages.iter().map(|_| Queue::new()).collect();
+
+        activity.lock().unwrap().running = stages.len();
+
+        let results = thread::scope(|s| {
+            let handles: Vec<_> = stages
+                .into_iter()
+                .zip(logs.iter().cloned())
+                .enumerate()
+                .map(|(no, (stage, log))| {
+                    let Stage {
+                        mut c,
+                        input: (sender, receiver),
+                        outputs,
+                    } = stage;
+
+                    // from now on only machines writing into the channel keep it open
+                    drop(sender);
+
+                    c.set_input_source(Inbox {
+                        receiver,
+                        activity: activity.clone(),
+                    });
+                    c.set_output_sink(Tap {
+                        senders: outputs,
+                        log,
+                        activity: activity.clone(),
+                    });
+
+                    let activity = activity.clone();
+
+                    s.spawn(move |_| {
+                        // a machine stops either on its end or when its input is over
+                        let result = c.run();
+
+                        let mut activity = activity.lock().unwrap();
+                        activity.running -= 1;
+
+                        let result = match result {
+                            Ok(Event::NeedInput) if !activity.halted => Err(format_err!(
+                                "Machine {} of the pipeline needs input which nobody is \
+                                 going to send",
+                                no
+                            )),
+                            Ok(event) => {
+                                activity.halted |= event == Event::Halted;
+                                Ok(())
+                            }
+                            Err(err) => Err(Error::from(err)),
+                        };
+
+                        // the halt is recorded by now, so machines reading from this
+                        // one stop cleanly once its outputs are closed
+                        drop(activity);
+                        drop(c);
+                        result
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .enumerate()
+                .map(|(no, handle)| match handle.join() {
+                    Ok(res) => res,
+                    Err(_) => Err(format_err!("Machine {} of the pipeline panicked", no)),
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .map_err(|_| format_err!("Pipeline thread panicked"))?;
+
+        results?;
+        Ok(logs.iter().map(Queue::drain).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use crate::computer::{assemble, parse_intcode, Computer};
+
+    // amplifiers from the examples of day 7
+    const CHAIN: &str = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+    const FEEDBACK: &str = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,\
+                            28,1005,28,6,99,0,0,5";
+
+    fn amplifiers(source: &str, phases: &[isize], feedback: bool) -> Pipeline {
+        let program = parse_intcode(source).unwrap();
+        let mut pipeline = Pipeline::new();
+
+        for phase in phases {
+            pipeline.add(Computer::new(&program, Some(vec![*phase])));
+        }
+        for amp in 1..phases.len() {
+            pipeline.connect(amp - 1, amp);
+        }
+        if feedback {
+            pipeline.connect(phases.len() - 1, 0);
+        }
+
+        pipeline.feed(0, 0);
+        pipeline
+    }
+
+    // passes input to output until there is no more input
+    const ECHO: &str = "loop: INP [x]\nPUT [x]\nJMPT #1, #loop\nx: DATA 0";
+
+    fn machine(source: &str) -> Computer {
+        Computer::new(&assemble(source).unwrap(), None)
+    }
+
+    #[test]
+    fn chain() {
+        let outputs = amplifiers(CHAIN, &[4, 3, 2, 1, 0], false).run().unwrap();
+        assert_eq!(outputs[4], vec![43210]);
+    }
+
+    #[test]
+    fn feedback_loop() {
+        let outputs = amplifiers(FEEDBACK, &[9, 8, 7, 6, 5], true).run().unwrap();
+        assert_eq!(outputs[4].last(), Some(&139_629_729));
+    }
+
+    #[test]
+    fn fan_out() {
+        let mut pipeline = Pipeline::new();
+        let source = pipeline.add(machine("PUT #1\nPUT #2\nPUT #0\nBRK"));
+        // doubles values until it gets 0
+        let double = "
+            loop: INP [x]
+                  JMPF [x], #end
+                  MUL [x], #2, [x]
+                  PUT [x]
+                  JMPT #1, #loop
+            end:  BRK
+            x:    DATA 0
+        ";
+
+        for _ in 0..2 {
+            let to = pipeline.add(machine(double));
+            pipeline.connect(source, to);
+        }
+
+        let outputs = pipeline.run().unwrap();
+        assert_eq!(outputs, vec![vec![1, 2, 0], vec![2, 4], vec![2, 4]]);
+    }
+
+    #[test]
+    fn halt_shuts_down() {
+        let mut pipeline = Pipeline::new();
+        let source = pipeline.add(machine("PUT #1\nPUT #2\nBRK"));
+        let first = pipeline.add(machine(ECHO));
+        let second = pipeline.add(machine(ECHO));
+        pipeline.connect(source, first).connect(first, second);
+
+        let outputs = pipeline.run().unwrap();
+        assert_eq!(outputs, vec![vec![1, 2], vec![1, 2], vec![1, 2]]);
+    }
+
+    #[test]
+    fn missing_input() {
+        let mut pipeline = Pipeline::new();
+        pipeline.add(machine("INP [0]\nINP [0]\nBRK"));
+        pipeline.feed(0, 1);
+
+        assert_eq!(
+            pipeline.run().unwrap_err().to_string(),
+            "Machine 0 of the pipeline needs input which nobody is going to send"
+        );
+    }
+
+    #[test]
+    fn loop_without_feed() {
+        let mut pipeline = Pipeline::new();
+        let first = pipeline.add(machine(ECHO));
+        let second = pipeline.add(machine(ECHO));
+        pipeline.connect(first, second).connect(second, first);
+
+        assert_eq!(
+            pipeline.run().unwrap_err().to_string(),
+            "Machine 0 of the pipeline needs input which nobody is going to send"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use super::disasm::{decode_at, Line};
+use super::op::Mode;
+use super::{ADD, EQ, INP, INSTRUCTIONS, LT, MUL};
+
+// what machines have executed, collected over all machines sharing the profile
+#[derive(Debug, Default)]
+pub(crate) struct Profile {
+    instructions: u64,
+    by_opcode: HashMap<isize, u64>,
+    by_ip: HashMap<usize, u64>,
+    // memory accessed by operands, fetching instructions isn't counted
+    reads: HashMap<usize, u64>,
+    writes: HashMap<usize, u64>,
+}
+
+pub(crate) type SharedProfile = Arc<Mutex<Profile>>;
+
+// index of the operand the instruction writes into
+fn written_operand(opcode: isize) -> Option<usize> {
+    match opcode {
+        ADD | MUL | LT | EQ => Some(2),
+        INP => Some(0),
+        _ => None,
+    }
+}
+
+fn mnemonic(opcode: isize) -> String {
+    INSTRUCTIONS
+        .iter()
+        .find(|(code, _, _)| *code == opcode)
+        .map_or_else(|| format!("?{}", opcode), |(_, name, _)| (*name).to_owned())
+}
+
+fn percent(count: u64, total: u64) -> f64 {
+    count as f64 * 100.0 / total.max(1) as f64
+}
+
+// the most frequent entries first, ties go in the order of keys
+fn hottest<K: Copy + Ord>(counts: &HashMap<K, u64>) -> Vec<(K, u64)> {
+    let mut sorted: Vec<(K, u64)> = counts.iter().map(|(k, v)| (*k, *v)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    sorted
+}
+
+// addresses read by operands of the instruction, offset is the relative base
+pub(super) fn operand_reads(line: &Line, opcode: isize, offset: isize) -> Vec<usize> {
+    match line {
+        Line::Instr { args, .. } => args
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| written_operand(opcode) != Some(*idx))
+            .filter_map(|(_, (value, mode))| match mode {
+                Mode::Direct => None,
+                Mode::Indirect => Some(*value as usize),
+                Mode::Relative => Some((offset + *value) as usize),
+            })
+            .collect(),
+        Line::Data { .. } => Vec::new(),
+    }
+}
+
+impl Profile {
+    pub(super) fn record(
+        &mut self,
+        ip: usize,
+        opcode: isize,
+        reads: &[usize],
+        writes: &[(usize, isize)],
+    ) {
+        self.instructions += 1;
+        *self.by_opcode.entry(opcode).or_insert(0) += 1;
+        *self.by_ip.entry(ip).or_insert(0) += 1;
+
+        for addr in reads {
+            *self.reads.entry(*addr).or_insert(0) += 1;
+        }
+        for (addr, _) in writes {
+            self.record_write(*addr);
+        }
+    }
+
+    // a write done apart from executing an instruction
+    pub(super) fn record_write(&mut self, addr: usize) {
+        *self.writes.entry(addr).or_insert(0) += 1;
+    }
+
+    // Executed instructions following each other in memory, every region comes
+    // along with the number of instructions executed in it
+    fn regions(&self, program: &[isize]) -> Vec<(u64, Vec<(Line, u64)>)> {
+        let mut ips: Vec<usize> = self.by_ip.keys().copied().collect();
+        ips.sort_unstable();
+
+        let mut regions: Vec<(u64, Vec<(Line, u64)>)> = Vec::new();
+        let mut next_ip = None;
+
+        for ip in ips {
+            let line = decode_at(program, ip);
+            let count = self.by_ip[&ip];
+            let end = ip + line.cells();
+
+            match regions.last_mut() {
+                Some((total, lines)) if next_ip == Some(ip) => {
+                    *total += count;
+                    lines.push((line, count));
+                }
+                _ => regions.push((count, vec![(line, count)])),
+            }
+
+            next_ip = Some(end);
+        }
+
+        regions.sort_by_key(|(total, _)| Reverse(*total));
+        regions
+    }
+
+    // Prints totals per opcode, the hottest regions of the program disassembled
+    // and the most accessed memory cells, `top` limits the regions and the cells
+    pub(crate) fn report(
+        &self,
+        program: &[isize],
+        top: usize,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let total = self.instructions;
+
+        writeln!(out, "instructions executed: {}\n", total)?;
+
+        writeln!(out, "{:<8} {:>12} {:>8}", "opcode", "executed", "share")?;
+        for (opcode, count) in hottest(&self.by_opcode) {
+            writeln!(
+                out,
+                "{:<8} {:>12} {:>7.2}%",
+                mnemonic(opcode),
+                count,
+                percent(count, total)
+            )?;
+        }
+
+        for (region_total, lines) in self.regions(program).into_iter().take(top) {
+            writeln!(
+                out,
+                "\nhot spot, {} instructions executed ({:.2}%):",
+                region_total,
+                percent(region_total, total)
+            )?;
+
+            for (line, count) in lines {
+                writeln!(out, "{:>12} {:>7.2}%   {}", count, percent(count, total), line)?;
+            }
+        }
+
+        let mut accessed: HashMap<usize, u64> = self.reads.clone();
+        for (addr, count) in &self.writes {
+            *accessed.entry(*addr).or_insert(0) += count;
+        }
+
+        writeln!(out, "\n{:<8} {:>12} {:>12}", "address", "reads", "writes")?;
+        for (addr, _) in hottest(&accessed).into_iter().take(top) {
+            writeln!(
+                out,
+                "{:<8} {:>12} {:>12}",
+                addr,
+                self.reads.get(&addr).unwrap_or(&0),
+                self.writes.get(&addr).unwrap_or(&0)
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::Path,
+};
+
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::versioned;
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+// Complete state of a machine, restoring it resumes the program exactly
+// where the snapshot was taken
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    pub(super) memory: Vec<isize>,
+    // snapshots taken before far memory existed have none
+    #[serde(default)]
+    pub(super) far_memory: BTreeMap<usize, isize>,
+    pub(super) ip: usize,
+    pub(super) offset: isize,
+
+    pub(super) stdin: isize,
+    pub(super) stdout: Option<isize>,
+    // input given on creation which hasn't been consumed yet
+    pub(super) input: Option<VecDeque<isize>>,
+
+    pub(super) finished: bool,
+    pub(super) wait_input: bool,
+    pub(super) input_dest: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    version: u32,
+    state: Snapshot,
+}
+
+impl Snapshot {
+    pub(crate) fn load(path: &Path) -> Result<Snapshot, Error> {
+        let file = versioned::load(path, "snapshot", SNAPSHOT_VERSION, |file: &SnapshotFile| {
+            file.version
+        })?;
+
+        Ok(file.state)
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<(), Error> {
+        let file = SnapshotFile {
+            version: SNAPSHOT_VERSION,
+            state: self.clone(),
+        };
+
+        fs::write(path, serde_json::to_string(&file)?)
+            .map_err(|err| format_err!("Can't write snapshot {}: {}", path.display(), err))
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+// one step of a traced machine
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TraceEvent {
+    Exec {
+        // machine which executed the instruction, in order of creation
+        machine: usize,
+        // disassembled instruction prefixed with its address
+        instr: String,
+        // values operands resolved to right before the execution
+        values: Vec<isize>,
+        // memory cells written by the instruction
+        writes: Vec<(usize, isize)>,
+        // new relative base if the instruction changed it
+        base: Option<isize>,
+    },
+    // input delivered to the instruction which had been waiting for it
+    Input {
+        machine: usize,
+        addr: usize,
+        value: isize,
+    },
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceEvent::Exec {
+                machine,
+                instr,
+                values,
+                writes,
+                base,
+            } => {
+                let mut notes = Vec::new();
+
+                if !values.is_empty() {
+                    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    notes.push(format!("values {}", values.join(", ")));
+                }
+                for (addr, value) in writes {
+                    notes.push(format!("[{}] <- {}", addr, value));
+                }
+                if let Some(base) = base {
+                    notes.push(format!("rb <- {}", base));
+                }
+
+                if notes.is_empty() {
+                    write!(f, "#{} {}", machine, instr)
+                } else {
+                    write!(f, "#{} {:<40} ; {}", machine, instr, notes.join(" ; "))
+                }
+            }
+
+            TraceEvent::Input {
+                machine,
+                addr,
+                value,
+            } => write!(f, "#{} input ; [{}] <- {}", machine, addr, value),
+        }
+    }
+}
+
+pub(crate) trait TraceSink: Send {
+    fn record(&mut self, event: TraceEvent);
+}
+
+// machines running on different threads may share one sink
+pub(crate) type SharedSink = Arc<Mutex<dyn TraceSink>>;
+
+// writes every event as a line of text, e.g. into a file
+pub(crate) struct WriterSink<W: Write + Send> {
+    out: W,
+    // the first error stops tracing, it must not break the traced program
+    failed: Option<io::Error>,
+}
+
+impl<W: Write + Send> WriterSink<W> {
+    pub(crate) fn new(out: W) -> Self {
+        WriterSink { out, failed: None }
+    }
+
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        match self.failed.take() {
+            Some(err) => Err(err),
+            None => self.out.flush(),
+        }
+    }
+}
+
+impl<W: Write + Send> TraceSink for WriterSink<W> {
+    fn record(&mut self, event: TraceEvent) {
+        if self.failed.is_none() {
+            if let Err(err) = writeln!(self.out, "{}", event) {
+                self.failed = Some(err);
+            }
+        }
+    }
+}
+
+// keeps only the last events, cheap enough for programs running billions of steps
+pub(crate) struct RingBuffer {
+    capacity: usize,
+    events: VecDeque<TraceEvent>,
+}
+
+impl RingBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        RingBuffer {
+            capacity,
+            // grows up to the capacity, which may be far more than a program ever executes
+            events: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter()
+    }
+}
+
+impl TraceSink for RingBuffer {
+    fn record(&mut self, event: TraceEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        if self.capacity > 0 {
+            self.events.push_back(event);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use colored::*;
+use failure::{format_err, Error};
+
+use crate::computer::{decode_at, Computer, Snapshot};
+
+const HELP: &str = "\
+Commands:
+    s, step [N]         execute N instructions, 1 by default
+    c, continue         run until a breakpoint, a watchpoint, missing input or the end
+    b, break ADDR       stop before executing the instruction at ADDR
+    d, delete ADDR      remove breakpoint at ADDR
+    w, watch ADDR       stop whenever the memory cell at ADDR changes
+    u, unwatch ADDR     remove watchpoint at ADDR
+    in VALUE...         queue values for the program input
+    out                 print everything the program has output so far
+    save NAME           remember the machine, queued input and output as NAME
+    load NAME           return the machine to the state saved as NAME
+    write PATH          store the current state of the machine in PATH
+    read PATH           resume the machine from the state stored in PATH
+    r, regs             print ip, relative base, stdin, stdout and state
+    x ADDR [N]          dump N memory cells starting at ADDR, 8 by default, 4096 at most
+    l, list [ADDR] [N]  disassemble N instructions starting at ADDR, ip and 10 by default,
+                        4096 at most
+    h, help             print this message
+    q, quit             leave the debugger";
+
+// most cells `x` dumps and instructions `list` prints at once
+const MAX_COUNT: usize = 4096;
+
+// why execution has stopped
+enum Stop {
+    Breakpoint(usize),
+    Watchpoint { addr: usize, old: isize, new: isize },
+    NeedInput,
+    Finished,
+}
+
+// state of the debugging session remembered by `save`
+struct Checkpoint {
+    snapshot: Snapshot,
+    // queued input which the machine hasn't read yet
+    input: VecDeque<isize>,
+    // output produced before the checkpoint
+    output: Vec<isize>,
+}
+
+struct Debugger {
+    c: Computer,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+    input: VecDeque<isize>,
+    output: Vec<isize>,
+    saved: HashMap<String, Checkpoint>,
+}
+
+fn parse_num<T: std::str::FromStr>(s: Option<&str>, what: &str) -> Result<T, Error> {
+    let s = s.ok_or_else(|| format_err!("Missing {}", what))?;
+    s.parse::<T>()
+        .map_err(|_| format_err!("Invalid {} `{}`", what, s))
+}
+
+fn parse_count(s: Option<&str>, default: usize) -> Result<usize, Error> {
+    let count = match s {
+        Some(n) => parse_num(Some(n), "count")?,
+        None => default,
+    };
+
+    if count > MAX_COUNT {
+        return Err(format_err!("Count can't exceed {}", MAX_COUNT));
+    }
+    Ok(count)
+}
+
+impl Debugger {
+    fn new(program: &[isize]) -> Self {
+        Debugger {
+            c: Computer::new(program, None),
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            input: VecDeque::new(),
+            output: Vec::new(),
+            saved: HashMap::new(),
+        }
+    }
+
+    fn current(&self) -> String {
+        decode_at(self.c.memory(), self.c.ip()).to_string()
+    }
+
+    fn watched(&self) -> Vec<(usize, isize)> {
+        self.watchpoints
+            .iter()
+            .map(|addr| (*addr, self.c.get_cell(*addr)))
+            .collect()
+    }
+
+    // executes one instruction feeding queued input and collecting output
+    fn exec_one(&mut self) -> Result<Option<Stop>, Error> {
+        if self.c.is_finished() {
+            return Ok(Some(Stop::Finished));
+        }
+
+        if self.c.waits_input() {
+            match self.input.pop_front() {
+                Some(value) => self.c.set_stdin(value),
+                None => return Ok(Some(Stop::NeedInput)),
+            }
+        }
+
+        let before = self.watched();
+        self.c.step_instr()?;
+
+        if let Some(value) = self.c.stdout.take() {
+            println!("{} {}", "output:".yellow(), value);
+            self.output.push(value);
+        }
+
+        for (addr, old) in before {
+            let new = self.c.get_cell(addr);
+            if new != old {
+                return Ok(Some(Stop::Watchpoint { addr, old, new }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn report(&self, stop: Stop) {
+        match stop {
+            Stop::Breakpoint(addr) => println!("{} at {}", "breakpoint".red(), addr),
+            Stop::Watchpoint { addr, old, new } => println!(
+                "{} [{}] changed from {} to {}",
+                "watchpoint".red(),
+                addr,
+                old,
+                new
+            ),
+            Stop::NeedInput => println!("program waits for input, queue it with `in`"),
+            Stop::Finished => println!("program has finished"),
+        }
+    }
+
+    fn step(&mut self, count: usize) -> Result<(), Error> {
+        for _ in 0..count {
+            println!("{}", self.current());
+
+            if let Some(stop) = self.exec_one()? {
+                self.report(stop);
+                return Ok(());
+            }
+        }
+
+        println!("{} {}", "next:".dimmed(), self.current());
+        Ok(())
+    }
+
+    fn cont(&mut self) -> Result<(), Error> {
+        loop {
+            if let Some(stop) = self.exec_one()? {
+                self.report(stop);
+                break;
+            }
+
+            if self.breakpoints.contains(&self.c.ip()) {
+                self.report(Stop::Breakpoint(self.c.ip()));
+                break;
+            }
+        }
+
+        println!("{} {}", "next:".dimmed(), self.current());
+        Ok(())
+    }
+
+    fn regs(&self) {
+        let state = if self.c.is_finished() {
+            "finished"
+        } else if self.c.waits_input() {
+            "waiting for input"
+        } else {
+            "running"
+        };
+
+        println!(
+            "ip: {}  rb: {}  stdin: {}  stdout: {:?}  state: {}  queued input: {:?}",
+            self.c.ip(),
+            self.c.offset(),
+            self.c.stdin,
+            self.output.last(),
+            state,
+            self.input
+        );
+    }
+
+    fn dump(&self, addr: usize, count: usize) -> Result<(), Error> {
+        let end = addr
+            .checked_add(count)
+            .ok_or_else(|| format_err!("Cells past the end of memory requested"))?;
+
+        for row in (addr..end).step_by(8) {
+            let cells: Vec<String> = (row..end.min(row.saturating_add(8)))
+                .map(|addr| format!("{:>8}", self.c.get_cell(addr)))
+                .collect();
+            println!("{:04}: {}", row, cells.join(" "));
+        }
+
+        Ok(())
+    }
+
+    fn list(&self, mut addr: usize, count: usize) {
+        for _ in 0..count {
+            let line = decode_at(self.c.memory(), addr);
+            let marker = if addr == self.c.ip() { "=>" } else { "  " };
+            let bp = if self.breakpoints.contains(&addr) { "*" } else { " " };
+
+            println!("{}{} {}", marker, bp.red(), line);
+
+            addr = match addr.checked_add(line.cells()) {
+                Some(next) => next,
+                // the end of memory
+                None => break,
+            };
+        }
+    }
+
+    // returns false when the user wants to quit
+    fn command(&mut self, line: &str) -> Result<bool, Error> {
+        let mut words = line.split_whitespace();
+
+        let cmd = match words.next() {
+            Some(cmd) => cmd,
+            None => return Ok(true),
+        };
+
+        match cmd {
+            "s" | "step" => {
+                let count = match words.next() {
+                    Some(n) => parse_num(Some(n), "count")?,
+                    None => 1,
+                };
+                self.step(count)?;
+            }
+
+            "c" | "continue" => self.cont()?,
+
+            "b" | "break" => {
+                self.breakpoints.insert(parse_num(words.next(), "address")?);
+            }
+
+            "d" | "delete" => {
+                let addr = parse_num(words.next(), "address")?;
+                if !self.breakpoints.remove(&addr) {
+                    return Err(format_err!("No breakpoint at {}", addr));
+                }
+            }
+
+            "w" | "watch" => {
+                self.watchpoints.insert(parse_num(words.next(), "address")?);
+            }
+
+            "u" | "unwatch" => {
+                let addr = parse_num(words.next(), "address")?;
+                if !self.watchpoints.remove(&addr) {
+                    return Err(format_err!("No watchpoint at {}", addr));
+                }
+            }
+
+            "in" => {
+                for word in words {
+                    self.input.push_back(parse_num(Some(word), "input value")?);
+                }
+            }
+
+            "out" => println!("{:?}", self.output),
+
+            "save" => {
+                let name = words.next().ok_or_else(|| format_err!("Missing name"))?;
+                let checkpoint = Checkpoint {
+                    snapshot: self.c.snapshot(),
+                    input: self.input.clone(),
+                    output: self.output.clone(),
+                };
+                self.saved.insert(name.to_owned(), checkpoint);
+            }
+
+            "load" => {
+                let name = words.next().ok_or_else(|| format_err!("Missing name"))?;
+                let checkpoint = self
+                    .saved
+                    .get(name)
+                    .ok_or_else(|| format_err!("Nothing saved as `{}`", name))?;
+
+                self.c.restore(&checkpoint.snapshot);
+                self.input = checkpoint.input.clone();
+                self.output = checkpoint.output.clone();
+                println!("{} {}", "next:".dimmed(), self.current());
+            }
+
+            "write" => {
+                let path = words.next().ok_or_else(|| format_err!("Missing path"))?;
+                self.c.snapshot().save(Path::new(path))?;
+            }
+
+            "read" => {
+                let path = words.next().ok_or_else(|| format_err!("Missing path"))?;
+                self.c.restore(&Snapshot::load(Path::new(path))?);
+
+                // output printed before the state was stored belongs to another session
+                self.output.clear();
+                println!("{} {}", "next:".dimmed(), self.current());
+            }
+
+            "r" | "regs" => self.regs(),
+
+            "x" => {
+                let addr = parse_num(words.next(), "address")?;
+                let count = parse_count(words.next(), 8)?;
+                self.dump(addr, count)?;
+            }
+
+            "l" | "list" => {
+                let addr = match words.next() {
+                    Some(addr) => parse_num(Some(addr), "address")?,
+                    None => self.c.ip(),
+                };
+                let count = parse_count(words.next(), 10)?;
+                self.list(addr, count);
+            }
+
+            "h" | "help" => println!("{}", HELP),
+
+            "q" | "quit" => return Ok(false),
+
+            s => return Err(format_err!("Unknown command `{}`, try `help`", s)),
+        }
+
+        Ok(true)
+    }
+}
+
+// Interactive debugger reading commands from stdin until `quit` or end of input
+pub(crate) fn run(program: &[isize]) -> Result<(), Error> {
+    let mut dbg = Debugger::new(program);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    println!("{} cells loaded, type `help` for the list of commands", program.len());
+    println!("{} {}", "next:".dimmed(), dbg.current());
+
+    loop {
+        print!("(icdb) ");
+        io::stdout().flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+
+        match dbg.command(&line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => println!("{}: {}", "error".bold().red(), err),
+        }
+    }
+
+    Ok(())
+}
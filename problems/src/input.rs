@@ -0,0 +1,77 @@
+use std::{
+    borrow::Cow,
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use failure::{format_err, Error};
+
+pub(crate) const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+// where puzzle input comes from
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Source {
+    // input compiled into the binary
+    Embedded,
+    File(PathBuf),
+    Stdin,
+    // directory with files named "day1.txt", "day2.txt", ...
+    Dir(PathBuf),
+}
+
+impl Source {
+    // explicit command line choice wins, otherwise AOC_INPUT_DIR is consulted
+    pub(crate) fn resolve(from_args: Option<Source>) -> Source {
+        match from_args {
+            Some(source) => source,
+            None => match env::var_os(INPUT_DIR_VAR) {
+                Some(dir) => Source::Dir(PathBuf::from(dir)),
+                None => Source::Embedded,
+            },
+        }
+    }
+}
+
+fn day_file(dir: &Path, day: usize) -> PathBuf {
+    dir.join(format!("day{}.txt", day))
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|err| format_err!("Can't read {}: {}", path.display(), err))
+}
+
+// files saved from the browser end with a newline which embedded inputs don't have
+fn normalize(raw: String) -> String {
+    raw.trim_end().to_owned()
+}
+
+pub(crate) fn load(
+    source: &Source,
+    day: usize,
+    embedded: &'static str,
+) -> Result<Cow<'static, str>, Error> {
+    Ok(match source {
+        Source::Embedded => Cow::Borrowed(embedded),
+
+        Source::File(path) => Cow::Owned(normalize(read_file(path)?)),
+
+        Source::Stdin => {
+            let mut raw = String::new();
+            io::stdin()
+                .read_to_string(&mut raw)
+                .map_err(|err| format_err!("Can't read stdin: {}", err))?;
+            Cow::Owned(normalize(raw))
+        }
+
+        Source::Dir(dir) => {
+            let path = day_file(dir, day);
+            if path.exists() {
+                Cow::Owned(normalize(read_file(&path)?))
+            } else {
+                // fall back to the embedded input for days missing in the directory
+                Cow::Borrowed(embedded)
+            }
+        }
+    })
+}
@@ -0,0 +1,96 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use failure::{Error, Fail};
+
+use utils::Stars;
+
+use crate::{
+    computer::{install_config, Config},
+    solution::Solved,
+    Solver,
+};
+
+#[derive(Debug)]
+pub(crate) enum TaskFailure {
+    Panic(String),
+    // timeout in millis
+    Timeout(u128),
+}
+
+impl fmt::Display for TaskFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskFailure::Panic(msg) => write!(f, "solver panicked: {}", msg),
+            TaskFailure::Timeout(millis) => write!(f, "solver timed out after {} millis", millis),
+        }
+    }
+}
+
+impl Fail for TaskFailure {}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    // panic!() with a literal gives &str, formatted message gives String
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_owned()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_owned()
+    }
+}
+
+// Runs solver on the current thread turning a panic into an error
+pub(crate) fn catch_panic(f: Solver, input: &str, stars: Stars) -> Result<Solved, Error> {
+    match panic::catch_unwind(AssertUnwindSafe(|| f.run(input, stars))) {
+        Ok(result) => result,
+        Err(payload) => Err(TaskFailure::Panic(panic_message(&*payload)).into()),
+    }
+}
+
+// Runs solver on a dedicated thread, so that neither a panic nor an endless loop
+// can take the calling worker down. Rust threads can't be killed, so a solver which
+// ran out of time is left running in the background until the process exits.
+pub(crate) fn run_isolated(
+    f: Solver,
+    input: Cow<'static, str>,
+    stars: Stars,
+    problem_no: usize,
+    timeout: Option<Duration>,
+    config: Config,
+) -> Result<Solved, Error> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::Builder::new()
+        .name(format!("problem {}", problem_no))
+        .spawn(move || {
+            // machines pick up their config from the thread they are created on
+            install_config(config);
+
+            // nobody listens anymore if the solver has timed out
+            tx.send(catch_panic(f, &input, stars)).ok();
+        })?;
+
+    let received = match timeout {
+        Some(timeout) => rx.recv_timeout(timeout),
+        None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+
+    match received {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            Err(TaskFailure::Timeout(timeout.unwrap_or_default().as_millis()).into())
+        }
+        // the thread always sends its result unless it's killed by a panic while panicking
+        Err(RecvTimeoutError::Disconnected) => {
+            Err(TaskFailure::Panic("solver thread died".to_owned()).into())
+        }
+    }
+}
@@ -1,4 +1,14 @@
+mod answers;
+mod bench;
+mod cli;
 mod computer;
+mod debugger;
+mod input;
+mod isolation;
+mod report;
+mod schedule;
+mod solution;
+mod versioned;
 
 mod problem1;
 mod problem10;
@@ -17,13 +27,35 @@ mod problem7;
 mod problem8;
 mod problem9;
 
-use std::{time::SystemTime, marker::Sync, sync::Arc};
+use std::{
+    borrow::Cow,
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
 use crossbeam::{queue::SegQueue, thread};
-use num_cpus;
 use colored::*;
-use failure::Error;
+use failure::{format_err, Error};
 
-use utils::RetTypes;
+use utils::Stars;
+
+use bench::{fmt_micros, BenchOptions};
+use cli::{Command, Options, PipeOptions, ProfileOptions, TraceOptions};
+use computer::{
+    assemble, disassemble, install_config, parse_intcode, Ascii, Chunk, Computer, Config, Event,
+    Network, NetworkState, Pipeline, Profile, ReaderInput, RingBuffer, SharedSink, TraceSink,
+    WriterOutput, WriterSink,
+};
+use input::Source;
+use report::{make_records, Format, Record, CSV_HEADER};
+use schedule::History;
+use solution::{Runnable, Solved};
 
 // problems
 use crate::problem1 as p1;
@@ -43,12 +75,37 @@ use crate::problem7 as p7;
 use crate::problem8 as p8;
 use crate::problem9 as p9;
 
-fn exec(f: &(dyn Fn() -> Result<RetTypes, Error>), problem_no: usize) {
+type Solver = &'static dyn Runnable;
+type Task = (Solver, Cow<'static, str>, usize);
 
-    let now = SystemTime::now();
-    let result = f();
-    let elapsed = now.elapsed().unwrap().as_millis();
+// solvers along with their embedded inputs, indexed by day - 1
+const PROBLEMS: [(Solver, &str); 16] = [
+    (&p1::Problem, p1::INPUT),
+    (&p2::Problem, p2::INPUT),
+    (&p3::Problem, p3::INPUT),
+    (&p4::Problem, p4::INPUT),
+    (&p5::Problem, p5::INPUT),
+    (&p6::Problem, p6::INPUT),
+    (&p7::Problem, p7::INPUT),
+    (&p8::Problem, p8::INPUT),
+    (&p9::Problem, p9::INPUT),
+    (&p10::Problem, p10::INPUT),
+    (&p11::Problem, p11::INPUT),
+    (&p12::Problem, p12::INPUT),
+    (&p13::Problem, p13::INPUT),
+    (&p14::Problem, p14::INPUT),
+    (&p15::Problem, p15::INPUT),
+    (&p16::Problem, p16::INPUT),
+];
+
+fn fmt_stage(name: &str, time: Option<Duration>) -> String {
+    match time {
+        Some(time) => format!("{}: {}", name, fmt_micros(time.as_secs_f64() * 1_000_000.0)),
+        None => format!("{}: -", name),
+    }
+}
 
+fn print_text(result: &Result<Solved, Error>, problem_no: usize, elapsed: u64) {
     match result {
         Err(err) => println!(
             "{} {}:\n{}: {}",
@@ -58,65 +115,507 @@ fn exec(f: &(dyn Fn() -> Result<RetTypes, Error>), problem_no: usize) {
             err
         ),
 
-        Ok(answer) => println!(
-            "{} {}:\n{}",
+        Ok(solved) => println!(
+            "{} {}:\n{}\n{}, {}, {}",
             "problem".bold(),
             problem_no.to_string().bold(),
-            answer
+            solved.answer,
+            fmt_stage("parse", Some(solved.timings.parse)),
+            fmt_stage("first star", solved.timings.part1),
+            fmt_stage("second star", solved.timings.part2)
         ),
     }
 
     println!("time elapsed for problem: {} millis\n", elapsed);
 }
 
-fn main() {
+// everything known about a finished problem
+struct Outcome {
+    problem_no: usize,
+    result: Result<Solved, Error>,
+    elapsed: u64,
+    worker: usize,
+    // known answers can be checked only for the embedded input
+    embedded: bool,
+}
+
+fn exec(
+    f: Solver,
+    input: Cow<'static, str>,
+    stars: Stars,
+    problem_no: usize,
+    worker: usize,
+    timeout: Option<Duration>,
+    config: &Config,
+) -> Outcome {
 
-    println!("\n{}\n\n", "Advent of code 2019".bold());
-
-    let q: Arc<SegQueue<(&(dyn Fn() -> Result<RetTypes, Error> + Sync), usize)>> = Arc::new(SegQueue::new());
-
-    q.push((&p1::solve, 1));
-    q.push((&p2::solve, 2));
-    q.push((&p3::solve, 3));
-    q.push((&p4::solve, 4));
-    q.push((&p5::solve, 5));
-    q.push((&p6::solve, 6));
-    q.push((&p7::solve, 7));
-    q.push((&p8::solve, 8));
-    q.push((&p9::solve, 9));
-    q.push((&p10::solve, 10));
-    q.push((&p11::solve, 11));
-    q.push((&p12::solve, 12));
-    q.push((&p13::solve, 13));
-    q.push((&p14::solve, 14));
-    q.push((&p15::solve, 15));
-    q.push((&p16::solve, 16));
-
-    println!("{} cores detected\n", num_cpus::get_physical());
+    // input is borrowed only if it's the embedded one
+    let embedded = matches!(input, Cow::Borrowed(_));
 
     let now = SystemTime::now();
+    let result = isolation::run_isolated(f, input, stars, problem_no, timeout, config.clone());
+    let elapsed = now.elapsed().unwrap().as_millis() as u64;
+
+    Outcome {
+        problem_no,
+        result,
+        elapsed,
+        worker,
+        embedded,
+    }
+}
+
+fn print_outcome(outcome: &Outcome, stars: Stars, format: Format) -> Vec<Record> {
+    let records = make_records(
+        outcome.problem_no,
+        stars,
+        &outcome.result,
+        outcome.elapsed,
+        outcome.worker,
+    );
+
+    match format {
+        Format::Text => print_text(&outcome.result, outcome.problem_no, outcome.elapsed),
+
+        Format::Json => {
+            for record in &records {
+                println!("{}", record.to_json());
+            }
+        }
+
+        Format::Csv => {
+            for record in &records {
+                println!("{}", record.to_csv());
+            }
+        }
+    }
+
+    records
+}
+
+// reports problems as they finish, goes to stderr to keep the report on stdout intact
+struct Progress {
+    enabled: bool,
+    total: usize,
+    done: AtomicUsize,
+}
+
+impl Progress {
+    fn finished(&self, outcome: &Outcome) {
+        let done = self.done.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if self.enabled {
+            let status = if outcome.result.is_ok() {
+                "done".green()
+            } else {
+                "failed".red()
+            };
+
+            eprintln!(
+                "[{}/{}] problem {} {} in {} millis (worker {})",
+                done, self.total, outcome.problem_no, status, outcome.elapsed, outcome.worker
+            );
+        }
+    }
+}
+
+// returns false if verification was requested and some answers are wrong
+fn verify(results: &[(Record, bool)], format: Format) -> Result<bool, Error> {
+    let verdicts: Vec<_> = results
+        .iter()
+        .map(|(record, embedded)| (record, answers::verify(record, *embedded)))
+        .collect();
+
+    // keep stdout parseable for machine readable formats
+    Ok(if format == Format::Text {
+        answers::report(&verdicts, &mut io::stdout())?
+    } else {
+        answers::report(&verdicts, &mut io::stderr())?
+    })
+}
+
+fn run(opts: Options) -> Result<(), Error> {
+
+    // inputs are loaded upfront so that a missing file is reported before anything runs
+    let mut tasks: Vec<Task> = Vec::new();
+    for day in &opts.days {
+        let (solver, embedded) = PROBLEMS[day - 1];
+        tasks.push((solver, input::load(&opts.input, *day, embedded)?, *day));
+    }
+
+    if let Some(settings) = &opts.bench {
+        // benchmarks run on this thread
+        install_config(Config::new().limits(opts.limits));
+
+        let tasks: Vec<_> = tasks
+            .iter()
+            .map(|(solver, input, day)| (*solver, input.as_ref(), *day))
+            .collect();
+
+        let ok = bench::run(
+            &tasks,
+            &BenchOptions {
+                runs: settings.runs,
+                stars: opts.stars,
+                save_to: settings.save_to.as_deref(),
+                compare_with: settings.compare_with.as_deref(),
+                threshold: settings.threshold,
+            },
+        )?;
+
+        return if ok {
+            Ok(())
+        } else {
+            Err(format_err!("Some problems failed or got slower than the baseline"))
+        };
+    }
+
+    let mut history = match &opts.timings {
+        Some(path) => Some(History::load(path)?),
+        None => None,
+    };
+
+    if let Some(history) = &history {
+        history.arrange(&mut tasks, opts.schedule);
+    }
+
+    let progress = Progress {
+        enabled: opts.progress,
+        total: tasks.len(),
+        done: AtomicUsize::new(0),
+    };
+
+    let q: Arc<SegQueue<Task>> = Arc::new(SegQueue::new());
+    let finished: Arc<SegQueue<Outcome>> = Arc::new(SegQueue::new());
+
+    for task in tasks {
+        q.push(task);
+    }
+
+    let format = opts.format;
+
+    match format {
+        Format::Text => {
+            println!("\n{}\n\n", "Advent of code 2019".bold());
+            println!(
+                "{} cores detected, running {} jobs\n",
+                num_cpus::get_physical(),
+                opts.jobs
+            );
+        }
+        Format::Csv => println!("{}", CSV_HEADER),
+        Format::Json => {}
+    }
+
+    let now = SystemTime::now();
+    let stars = opts.stars;
+    let timeout = opts.timeout;
+    let config = Config::new().limits(opts.limits);
 
     thread::scope(|s| {
-        for idx in 0..num_cpus::get_physical() {
+        for idx in 0..opts.jobs {
             let q = Arc::clone(&q);
+            let finished = Arc::clone(&finished);
+            let progress = &progress;
+            let config = &config;
             s.spawn(move |_| {
-                while let Ok((task, task_id)) = q.pop() {
-                    println!("Worker {} executing problem {}\n", idx, task_id);
-                    exec(task, task_id);
+                while let Ok((task, input, task_id)) = q.pop() {
+                    let outcome = exec(task, input, stars, task_id, idx, timeout, config);
+                    progress.finished(&outcome);
+                    finished.push(outcome);
                 }
             });
         }
     }).unwrap();
 
-    println!(
-        "{} {} {}",
-        "Total time taken:".bold(),
-        now.elapsed()
-            .unwrap()
-            .as_millis()
-            .to_string()
-            .bold()
-            .green(),
-        "millis".bold()
-    );
+    // problems finish in arbitrary order, the report is always ordered by day
+    let mut outcomes = Vec::new();
+    while let Ok(outcome) = finished.pop() {
+        outcomes.push(outcome);
+    }
+    outcomes.sort_by_key(|outcome| outcome.problem_no);
+
+    let mut results = Vec::new();
+    for outcome in &outcomes {
+        for record in print_outcome(outcome, stars, format) {
+            results.push((record, outcome.embedded));
+        }
+    }
+
+    if format == Format::Text {
+        println!(
+            "{} {} {}\n",
+            "Total time taken:".bold(),
+            now.elapsed()
+                .unwrap()
+                .as_millis()
+                .to_string()
+                .bold()
+                .green(),
+            "millis".bold()
+        );
+    }
+
+    if let (Some(history), Some(path)) = (&mut history, &opts.timings) {
+        // failed problems tell nothing about how long they usually take
+        for outcome in outcomes.iter().filter(|outcome| outcome.result.is_ok()) {
+            history.record(outcome.problem_no, outcome.elapsed);
+        }
+        history.save(path)?;
+    }
+
+    if opts.verify && !verify(&results, format)? {
+        return Err(format_err!("Some answers didn't pass verification"));
+    }
+
+    Ok(())
+}
+
+fn parse_program(day: usize, input: &str) -> Result<Vec<isize>, Error> {
+    parse_intcode(input)
+        .map_err(|err| format_err!("Input of day {} is not an Intcode program: {}", day, err))
+}
+
+fn load_program(day: usize, source: &Source) -> Result<Vec<isize>, Error> {
+    let (_, embedded) = PROBLEMS[day - 1];
+    parse_program(day, &input::load(source, day, embedded)?)
+}
+
+fn disasm(day: usize, source: &Source) -> Result<(), Error> {
+    let program = load_program(day, source)?;
+
+    for line in disassemble(&program) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+fn asm(source: &Source) -> Result<(), Error> {
+    // the source is always a file or stdin, so there is no day and no embedded input
+    let program = assemble(&input::load(source, 0, "")?)?;
+
+    let cells: Vec<String> = program.iter().map(|cell| cell.to_string()).collect();
+    println!("{}", cells.join(","));
+
+    Ok(())
+}
+
+// runs an assembled program on a network of machines until it settles down
+fn net(source: &Source, size: usize) -> Result<(), Error> {
+    let program = assemble(&input::load(source, 0, "")?)?;
+    let mut network = Network::new(&program, size);
+
+    // y of the last packet the NAT has sent
+    let mut last_y = None;
+
+    loop {
+        let (sent, state) = network.round()?;
+
+        for packet in sent {
+            println!("{}", packet);
+        }
+
+        match state {
+            NetworkState::Busy => {}
+
+            NetworkState::Halted => {
+                println!("all machines halted");
+                return Ok(());
+            }
+
+            NetworkState::Idle => match network.wake()? {
+                Some(packet) => {
+                    println!("{}", packet);
+
+                    if last_y == Some(packet.y) {
+                        println!("NAT sent y {} twice in a row", packet.y);
+                        return Ok(());
+                    }
+                    last_y = Some(packet.y);
+                }
+                None => return Err(format_err!("Network is idle and the NAT has nothing to send")),
+            },
+        }
+    }
+}
+
+// runs the program of a day as a filter from stdin to stdout
+fn execute(day: usize, source: &Source) -> Result<(), Error> {
+    let program = load_program(day, source)?;
+
+    let mut c = Computer::new(&program, None);
+    c.set_input_source(ReaderInput::new(BufReader::new(io::stdin())));
+    c.set_output_sink(WriterOutput(io::stdout()));
+    if c.run()? != Event::Halted {
+        return Err(format_err!("Program of day {} needs more input", day));
+    }
+
+    Ok(())
+}
+
+// connects a program talking in ASCII to the terminal
+fn ascii(day: usize, source: &Source) -> Result<(), Error> {
+    let program = load_program(day, source)?;
+    let mut machine = Ascii::new(Computer::new(&program, None));
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        for chunk in machine.run()? {
+            match chunk {
+                Chunk::Text(text) => print!("{}", text),
+                Chunk::Value(value) => println!("{}", value.to_string().bold()),
+            }
+        }
+        io::stdout().flush()?;
+
+        if machine.is_finished() {
+            return Ok(());
+        }
+
+        match lines.next() {
+            Some(line) => machine.send_line(&line?),
+            // nothing more to say to the program
+            None => return Ok(()),
+        }
+    }
+}
+
+// runs copies of the program of a day on their own threads, one after another
+fn pipe(opts: &PipeOptions) -> Result<(), Error> {
+    let program = load_program(opts.day, &opts.input)?;
+    let mut pipeline = Pipeline::new();
+
+    for value in &opts.init {
+        pipeline.add(Computer::new(&program, Some(vec![*value])));
+    }
+    for to in 1..opts.init.len() {
+        pipeline.connect(to - 1, to);
+    }
+    if opts.feedback {
+        pipeline.connect(opts.init.len() - 1, 0);
+    }
+    for value in &opts.feed {
+        pipeline.feed(0, *value);
+    }
+
+    if let Some(output) = pipeline.run()?.last() {
+        for value in output {
+            println!("{}", value);
+        }
+    }
+
+    Ok(())
+}
+
+// solves a day with tracing installed, so all its Intcode machines are traced
+fn traced(opts: &TraceOptions, input: &str, sink: SharedSink) -> Result<Solved, Error> {
+    let (solver, _) = PROBLEMS[opts.day - 1];
+
+    install_config(Config::new().trace(sink));
+    let result = isolation::catch_panic(solver, input, opts.stars);
+    install_config(Config::new());
+
+    result
+}
+
+fn trace(opts: &TraceOptions) -> Result<(), Error> {
+    let (_, embedded) = PROBLEMS[opts.day - 1];
+    let input = input::load(&opts.input, opts.day, embedded)?;
+
+    let out: Box<dyn Write + Send> = match &opts.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(|err| {
+            format_err!("Can't create {}: {}", path.display(), err)
+        })?)),
+        None => Box::new(io::stdout()),
+    };
+    let writer = Arc::new(Mutex::new(WriterSink::new(out)));
+
+    let result = match opts.last {
+        Some(last) => {
+            let ring = Arc::new(Mutex::new(RingBuffer::new(last)));
+            let result = traced(opts, &input, ring.clone());
+
+            let mut writer = writer.lock().unwrap();
+            for event in ring.lock().unwrap().events() {
+                writer.record(event.clone());
+            }
+            result
+        }
+
+        None => traced(opts, &input, writer.clone()),
+    };
+
+    writer.lock().unwrap().finish()?;
+
+    // the trace may go to stdout, the answer goes to stderr to keep it apart
+    eprintln!("{}", result?.answer);
+    Ok(())
+}
+
+// solves a day with profiling installed, so all its Intcode machines are profiled
+fn profile(opts: &ProfileOptions) -> Result<(), Error> {
+    let (solver, embedded) = PROBLEMS[opts.day - 1];
+    let input = input::load(&opts.input, opts.day, embedded)?;
+    // hot spots are disassembled from the program as it was given
+    let program = parse_program(opts.day, &input)?;
+
+    let profile = Arc::new(Mutex::new(Profile::default()));
+
+    install_config(Config::new().profile(profile.clone()));
+    let result = isolation::catch_panic(solver, &input, opts.stars);
+    install_config(Config::new());
+
+    profile
+        .lock()
+        .unwrap()
+        .report(&program, opts.top, &mut io::stdout())?;
+
+    // the answer goes to stderr to keep the report apart, the same as for traces
+    eprintln!("{}", result?.answer);
+    Ok(())
+}
+
+// reports a failed command the same way for all of them
+fn exit_on_error(result: Result<(), Error>) {
+    if let Err(err) = result {
+        eprintln!("{}: {}", "error".bold().red(), err);
+        process::exit(1);
+    }
+}
+
+fn main() {
+
+    match cli::parse_args(env::args().skip(1), PROBLEMS.len()) {
+        Ok(Command::Run(opts)) => exit_on_error(run(opts)),
+
+        Ok(Command::Disasm { day, input }) => exit_on_error(disasm(day, &input)),
+
+        Ok(Command::Asm { input }) => exit_on_error(asm(&input)),
+
+        Ok(Command::Net { input, size }) => exit_on_error(net(&input, size)),
+
+        Ok(Command::Debug { day, input }) => {
+            exit_on_error(load_program(day, &input).and_then(|program| debugger::run(&program)))
+        }
+
+        Ok(Command::Exec { day, input }) => exit_on_error(execute(day, &input)),
+
+        Ok(Command::Ascii { day, input }) => exit_on_error(ascii(day, &input)),
+
+        Ok(Command::Pipe(opts)) => exit_on_error(pipe(&opts)),
+
+        Ok(Command::Trace(opts)) => exit_on_error(trace(&opts)),
+
+        Ok(Command::Profile(opts)) => exit_on_error(profile(&opts)),
+
+        Ok(Command::Help) => println!("{}", cli::USAGE),
+
+        Err(err) => {
+            eprintln!("{}: {}\n\n{}", "error".bold().red(), err, cli::USAGE);
+            process::exit(2);
+        }
+    }
 }
@@ -1,13 +1,11 @@
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, ProblemResult, RetTypes};
+use failure::format_err;
+use utils::{split_by_lines, ParseResult, ProblemResult};
+
+use crate::solution::Solution;
 
 fn fuel_req(mass: usize) -> usize {
     let a = mass / 3;
-    if a >= 2 {
-        a - 2
-    } else {
-        0
-    }
+    a.saturating_sub(2)
 }
 
 fn first_star(input: &[usize]) -> ProblemResult<usize> {
@@ -32,17 +30,26 @@ fn second_star(input: &[usize]) -> ProblemResult<usize> {
     Ok(fuel)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input: Vec<usize> = split_by_lines(input_raw, &|e: &str| {
-        e.parse::<usize>()
-            .or_else(|_| Err(format_err!("Failed to parse input")))
-    })?;
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
+
+impl Solution for Problem {
+    type Input = Vec<usize>;
+    type First = usize;
+    type Second = usize;
 
-    Ok(
-        RetTypes::Usize(
-            result(first_star(&input), second_star(&input))
-        )
-    )
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<usize>> {
+        split_by_lines(input_raw, &|e: &str| {
+            e.parse::<usize>().map_err(|_| format_err!("Failed to parse input"))
+        })
+    }
+
+    fn part1(&self, input: &Vec<usize>) -> ProblemResult<usize> {
+        first_star(input)
+    }
 
+    fn part2(&self, input: &Vec<usize>) -> ProblemResult<usize> {
+        second_star(input)
+    }
 }
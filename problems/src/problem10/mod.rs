@@ -1,14 +1,13 @@
 use std::collections::{HashSet, VecDeque};
-use std::{f64::EPSILON, fmt};
-
-use failure::Error;
+use std::fmt;
 
+use crate::solution::Solution;
 use utils::{
-    dot_product_2d, normalize_2d, result, split_by, split_by_lines, vec_product_2d, ProblemResult,
-    RetTypes,
+    dot_product_2d, normalize_2d, split_by, split_by_lines, vec_product_2d, ParseResult,
+    ProblemResult,
 };
 
-const PREC: f64 = 1f64 - EPSILON;
+const PREC: f64 = 1f64 - f64::EPSILON;
 const UP_VEC: (f64, f64) = (0f64, 1f64);
 const TO_DESTROY: usize = 200;
 
@@ -22,7 +21,7 @@ struct AngleCoords {
 }
 
 #[derive(Copy, Clone, PartialEq)]
-enum Point {
+pub(crate) enum Point {
     Asteroid,
     Empty,
 }
@@ -205,7 +204,7 @@ fn first_star(field: &[Vec<Point>]) -> ProblemResult<(usize, Coords<usize>)> {
     Ok((max_visible, station_coords))
 }
 
-fn second_start(field: &mut Vec<Vec<Point>>, base_coords: Coords<usize>) -> ProblemResult<usize> {
+fn second_start(field: &mut [Vec<Point>], base_coords: Coords<usize>) -> ProblemResult<usize> {
     let mut destroyed = 0;
 
     loop {
@@ -227,27 +226,34 @@ fn second_start(field: &mut Vec<Vec<Point>>, base_coords: Coords<usize>) -> Prob
     }
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let mut input = split_by_lines(input_raw, &|line: &str| {
-        Ok(split_by(line, "", &|e: &str| {
-            Ok(if e == "." {
-                Point::Empty
-            } else {
-                Point::Asteroid
-            })
-        })?)
-    })?;
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
+
+impl Solution for Problem {
+    type Input = Vec<Vec<Point>>;
+    type First = usize;
+    type Second = usize;
 
-    let (max_visible, station_coords) = first_star(&input)?;
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<Vec<Point>>> {
+        split_by_lines(input_raw, &|line: &str| {
+            split_by(line, "", &|e: &str| {
+                Ok(if e == "." {
+                    Point::Empty
+                } else {
+                    Point::Asteroid
+                })
+            })
+        })
+    }
 
-    Ok(
-        RetTypes::Usize(
-            result(
-                Ok(max_visible),
-                second_start(&mut input, station_coords),
-            )
-        )
-    )
+    fn part1(&self, field: &Vec<Vec<Point>>) -> ProblemResult<usize> {
+        Ok(first_star(field)?.0)
+    }
 
+    fn part2(&self, field: &Vec<Vec<Point>>) -> ProblemResult<usize> {
+        // station location is found the same way as in the first star
+        let (_, station_coords) = first_star(field)?;
+        second_start(&mut field.clone(), station_coords)
+    }
 }
@@ -1,10 +1,10 @@
+use failure::format_err;
 use rand::prelude::*;
 use std::collections::HashMap;
 
-use failure::Error;
-
-use crate::computer::{parse_intcode, Computer};
-use utils::{result, ProblemResult, RetTypes};
+use crate::computer::{parse_intcode, Computer, Queue};
+use crate::solution::Solution;
+use utils::{ParseResult, ProblemResult};
 
 #[derive(Hash, Eq, PartialEq)]
 struct Coords {
@@ -80,26 +80,34 @@ fn drawer(program: &[isize], board: &mut HashMap<Coords, Color>) -> ProblemResul
 
     let mut direct = 0;
 
-    let mut c = Computer::new(program, None);
-
-    loop {
-        c.step()?;
-
-        if c.is_finished() {
-            break;
-        } else if c.waits_input() {
-            c.set_stdin(isize::from(
-                *board.get(&Coords { x, y }).unwrap_or(&Color::from(0)),
-            ));
-            c.step()?;
-        }
-
-        let new_color: Color = c.get_output()?.into();
-        board.insert(Coords { x, y }, new_color);
+    let input = Queue::new();
+    let output = Queue::new();
 
-        c.step()?;
+    let mut c = Computer::new(program, None);
+    c.set_input_source(input.clone());
+    c.set_output_sink(output.clone());
+
+    while !c.is_finished() {
+        input.push(isize::from(
+            *board.get(&Coords { x, y }).unwrap_or(&Color::from(0)),
+        ));
+
+        // robot paints and turns once per input and then asks for the next one
+        c.run()?;
+
+        let (new_color, turn) = match output.drain()[..] {
+            [new_color, turn] => (new_color, turn),
+            // the robot may stop without painting anything more
+            [] if c.is_finished() => break,
+            ref other => {
+                return Err(format_err!(
+                    "Robot must output a color and a turn, got {:?}",
+                    other
+                ))
+            }
+        };
 
-        let turn: Turn = c.get_output()?.into();
+        board.insert(Coords { x, y }, Color::from(new_color));
 
         // assume following:
         // ------------------
@@ -108,7 +116,7 @@ fn drawer(program: &[isize], board: &mut HashMap<Coords, Color>) -> ProblemResul
         // Down = 2
         // Left = 3
 
-        direct += match turn {
+        direct += match Turn::from(turn) {
             Turn::Right => 1,
             Turn::Left => -1,
         };
@@ -146,19 +154,19 @@ fn second_star(program: &[isize]) -> ProblemResult<String> {
 
     let min_x = board
         .keys()
-        .fold(std::isize::MAX, |min, e| if min > e.x { e.x } else { min });
+        .fold(isize::MAX, |min, e| if min > e.x { e.x } else { min });
 
     let min_y = board
         .keys()
-        .fold(std::isize::MAX, |min, e| if min > e.y { e.y } else { min });
+        .fold(isize::MAX, |min, e| if min > e.y { e.y } else { min });
 
     let max_x = board
         .keys()
-        .fold(std::isize::MIN, |max, e| if max < e.x { e.x } else { max });
+        .fold(isize::MIN, |max, e| if max < e.x { e.x } else { max });
 
     let max_y = board
         .keys()
-        .fold(std::isize::MIN, |max, e| if max < e.y { e.y } else { max });
+        .fold(isize::MIN, |max, e| if max < e.y { e.y } else { max });
 
     let width = max_x - min_x;
     let height = max_y - min_y + 1;
@@ -205,17 +213,24 @@ fn second_star(program: &[isize]) -> ProblemResult<String> {
     Ok(filename.to_owned())
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
 
-    Ok(
-        RetTypes::UsizeString(
-            result(
-                first_star(&input),
-                second_star(&input),
-            )
-        )
-    )
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = usize;
+    type Second = String;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
 
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<usize> {
+        first_star(program)
+    }
+
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<String> {
+        second_star(program)
+    }
 }
@@ -1,8 +1,9 @@
 use std::fmt;
-use failure::Error;
 
 use num_integer::Integer;
-use utils::{result, split_by_lines, ProblemResult, RetTypes};
+
+use crate::solution::Solution;
+use utils::{split_by_lines, ParseResult, ProblemResult};
 
 const SIM_STEPS: usize = 1000;
 const X: usize = 0;
@@ -10,7 +11,7 @@ const Y: usize = 1;
 const Z: usize = 2;
 
 #[derive(Debug, Default, Copy, Clone)]
-struct Point3 {
+pub(crate) struct Point3 {
     x: isize,
     y: isize,
     z: isize,
@@ -83,7 +84,7 @@ fn update_coords(objects: &mut [Point3], vel: &[Point3]) {
     }
 }
 
-fn first_star(mut objects: &mut Vec<Point3>) -> ProblemResult<usize> {
+fn first_star(objects: &mut [Point3]) -> ProblemResult<usize> {
     let mut vel = vec![
         Point3::default(),
         Point3::default(),
@@ -95,15 +96,15 @@ fn first_star(mut objects: &mut Vec<Point3>) -> ProblemResult<usize> {
         // apply gravity
         for (idx1, _) in objects.iter().enumerate() {
             for (idx2, _) in objects.iter().enumerate().skip(idx1 + 1) {
-                update_vel(&objects, &mut vel, idx1, idx2);
+                update_vel(objects, &mut vel, idx1, idx2);
             }
         }
 
         // apply velocities
-        update_coords(&mut objects, &vel);
+        update_coords(objects, &vel);
     }
 
-    let energy = compute_energy(&objects, &vel);
+    let energy = compute_energy(objects, &vel);
 
     Ok(energy)
 }
@@ -126,25 +127,25 @@ fn update_component(iter: usize, e: &mut IterDelta) {
     }
 }
 
-fn second_star(mut objects: &mut Vec<Point3>) -> ProblemResult<usize> {
-    let start_pos = objects.clone();
+fn second_star(objects: &mut [Point3]) -> ProblemResult<usize> {
+    let start_pos = objects.to_owned();
 
     let mut iter = 0;
 
     let mut vel = vec![Point3::default(); objects.len()];
-    let mut periods = vec![IterDelta::default(); 3];
+    let mut periods = [IterDelta::default(); 3];
 
     // find periods
     loop {
         // apply gravity
         for (idx1, _) in objects.iter().enumerate() {
             for (idx2, _) in objects.iter().enumerate().skip(idx1 + 1) {
-                update_vel(&objects, &mut vel, idx1, idx2);
+                update_vel(objects, &mut vel, idx1, idx2);
             }
         }
 
         // apply velocities
-        update_coords(&mut objects, &vel);
+        update_coords(objects, &vel);
 
         // check periods
         if objects
@@ -184,32 +185,39 @@ fn second_star(mut objects: &mut Vec<Point3>) -> ProblemResult<usize> {
     Ok(lcm)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
+pub(crate) const INPUT: &str = include_str!("./input");
 
-    let mut input = split_by_lines(input_raw, &|line: &str| {
-        let trimmed = &line[1..line.len() - 1];
-        let mut tmp = Vec::with_capacity(3);
+pub(crate) struct Problem;
 
-        for part in trimmed.split(',').collect::<Vec<&str>>() {
-            tmp.push(part.split('=').collect::<Vec<&str>>()[1].parse::<isize>()?);
-        }
+impl Solution for Problem {
+    type Input = Vec<Point3>;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<Point3>> {
+        split_by_lines(input_raw, &|line: &str| {
+            let trimmed = &line[1..line.len() - 1];
+            let mut tmp = Vec::with_capacity(3);
+
+            for part in trimmed.split(',').collect::<Vec<&str>>() {
+                tmp.push(part.split('=').collect::<Vec<&str>>()[1].parse::<isize>()?);
+            }
+
+            let p = Point3 {
+                x: tmp[0],
+                y: tmp[1],
+                z: tmp[2],
+            };
 
-        let p = Point3 {
-            x: tmp[0],
-            y: tmp[1],
-            z: tmp[2],
-        };
-
-        Ok(p)
-    })?;
-
-    Ok(
-        RetTypes::Usize(
-            result(
-                first_star(&mut input),
-                second_star(&mut input),
-            )
-        )
-    )
+            Ok(p)
+        })
+    }
+
+    fn part1(&self, objects: &Vec<Point3>) -> ProblemResult<usize> {
+        first_star(&mut objects.clone())
+    }
+
+    fn part2(&self, objects: &Vec<Point3>) -> ProblemResult<usize> {
+        second_star(&mut objects.clone())
+    }
 }
@@ -1,8 +1,10 @@
 use std::cmp::Ordering;
-use failure::Error;
 
-use crate::computer::{parse_intcode, stop_or_input, Computer};
-use utils::{result, ProblemResult, RetTypes};
+use failure::format_err;
+
+use crate::computer::{parse_intcode, Computer, Event};
+use crate::solution::Solution;
+use utils::{ParseResult, ProblemResult};
 
 const BLOCK: usize = 2;
 
@@ -32,71 +34,73 @@ impl AI {
 }
 
 fn first_star(program: &[isize]) -> ProblemResult<usize> {
-    let mut blocks_count = 0;
     let mut c = Computer::new(program, None);
+    let tiles = c.run_until_halt()?;
+
+    // every tile is drawn as x, y and tile id
+    let chunks = tiles.chunks_exact(3);
+    if !chunks.remainder().is_empty() {
+        return Err(format_err!(
+            "Game output of length {} doesn't split into tiles of three values",
+            tiles.len()
+        ));
+    }
 
-    Ok(loop {
-        c.step()?;
-        if c.is_finished() {
-            break blocks_count;
-        }
-
-        c.get_output().unwrap();
-
-        c.step()?;
-        c.get_output().unwrap();
-
-        c.step()?;
-        let title_id = c.get_output().unwrap() as usize;
-
-        if title_id == BLOCK {
-            blocks_count += 1;
-        }
-    })
+    Ok(chunks.filter(|tile| tile[2] as usize == BLOCK).count())
 }
 
-fn second_star(program: &mut [isize]) -> ProblemResult<usize> {
+fn second_star(program: &[isize]) -> ProblemResult<usize> {
     // setup initial state
+    let mut program = program.to_vec();
     program[0] = 2;
 
-    let mut c = Computer::new(program, None);
+    let mut c = Computer::new(&program, None);
 
     let mut x = 0;
     let mut score = 0;
+    let mut tile = Vec::with_capacity(3);
 
     let mut ai = AI { pad_x: 22 };
 
     Ok(loop {
-        c.step()?;
+        match c.run()? {
+            Event::Halted => break score,
 
-        if stop_or_input(&mut c, || ai.get_move_dir(x))? {
-            break score;
-        }
-        x = c.get_output()?;
+            Event::NeedInput => c.set_stdin(ai.get_move_dir(x)),
 
-        c.step()?;
-        let y = c.get_output()?;
+            Event::Output(value) => {
+                tile.push(value);
 
-        c.step()?;
-        if x == -1 && y == 0 {
-            score = c.get_output()? as usize;
+                if let [tile_x, y, id] = tile[..] {
+                    if tile_x == -1 && y == 0 {
+                        score = id as usize;
+                    }
+                    x = tile_x;
+                    tile.clear();
+                }
+            }
         }
     })
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let mut input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
 
-    let r1 = first_star(&input);
-    let r2 = second_star(&mut input);
+pub(crate) struct Problem;
 
-    assert_eq!(*r1.as_ref().unwrap(), 326);
-    assert_eq!(*r2.as_ref().unwrap(), 15_988);
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = usize;
+    type Second = usize;
 
-    Ok(
-        RetTypes::Usize(
-            result(r1, r2)
-        )
-    )
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
+
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<usize> {
+        first_star(program)
+    }
+
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<usize> {
+        second_star(program)
+    }
 }
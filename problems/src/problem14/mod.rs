@@ -1,10 +1,10 @@
-use failure::Error;
 use std::{collections::HashMap, fmt, cmp::Ordering};
 
 use itertools::Itertools;
 use std::collections::hash_map::Entry;
 
-use utils::{result, split_by_lines, ParseResult, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{split_by_lines, ParseResult, ProblemResult};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 struct Term {
@@ -19,7 +19,7 @@ impl fmt::Display for Term {
 }
 
 #[derive(Debug, Clone)]
-struct Formulae {
+pub(crate) struct Formulae {
     left: Vec<Term>,
     right: Term,
 }
@@ -149,7 +149,7 @@ fn find_max(dep_map: &HashMap<String, Formulae>, mut cur_fuel: usize, max_ore: u
     let mut step = 1;
 
     loop {
-        let res = compute_ore_consumption(&dep_map, &mut reserve, cur_fuel);
+        let res = compute_ore_consumption(dep_map, &mut reserve, cur_fuel);
 
         if res.ore >= max_ore {
             break res.items;
@@ -173,17 +173,17 @@ fn second_star(dep_map: &HashMap<String, Formulae>) -> ProblemResult<usize> {
     loop {
         let cur_fuel = min_fuel + (max_fuel - min_fuel) / 2;
 
-        let res = compute_ore_consumption(&dep_map, &mut reserve, cur_fuel);
+        let res = compute_ore_consumption(dep_map, &mut reserve, cur_fuel);
 
         match res.ore.cmp(&ore_avail) {
             Ordering::Greater => {
-                if compute_ore_consumption(&dep_map, &mut reserve, cur_fuel - 1).ore <= ore_avail {
+                if compute_ore_consumption(dep_map, &mut reserve, cur_fuel - 1).ore <= ore_avail {
                     return Ok(cur_fuel - 1);
                 }
                 max_fuel = res.items;
             }
             Ordering::Less =>  {
-                if compute_ore_consumption(&dep_map, &mut reserve, cur_fuel + 1).ore >= ore_avail {
+                if compute_ore_consumption(dep_map, &mut reserve, cur_fuel + 1).ore >= ore_avail {
                     return Ok(cur_fuel);
                 }
                 min_fuel = res.items;
@@ -195,19 +195,25 @@ fn second_star(dep_map: &HashMap<String, Formulae>) -> ProblemResult<usize> {
     }
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
+pub(crate) const INPUT: &str = include_str!("./input");
 
-    let formulas = split_by_lines(input_raw, &parse_line)?;
-    let dep_map = make_dep_map(&formulas);
+pub(crate) struct Problem;
 
-    Ok(
-        RetTypes::Usize(
-            result(
-                first_star(&dep_map),
-                second_star(&dep_map)
-            )
-        )
-    )
+impl Solution for Problem {
+    type Input = HashMap<String, Formulae>;
+    type First = usize;
+    type Second = usize;
 
+    fn parse(&self, input_raw: &str) -> ParseResult<HashMap<String, Formulae>> {
+        let formulas = split_by_lines(input_raw, &parse_line)?;
+        Ok(make_dep_map(&formulas))
+    }
+
+    fn part1(&self, dep_map: &HashMap<String, Formulae>) -> ProblemResult<usize> {
+        first_star(dep_map)
+    }
+
+    fn part2(&self, dep_map: &HashMap<String, Formulae>) -> ProblemResult<usize> {
+        second_star(dep_map)
+    }
 }
@@ -1,8 +1,10 @@
-use failure::Error;
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use failure::format_err;
+
 use crate::computer::{parse_intcode, Computer};
-use utils::{result, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{ParseResult, ProblemResult};
 
 #[derive(Copy, Clone)]
 enum Dir {
@@ -50,14 +52,6 @@ impl Dir {
         }
     }
 
-    fn opposite_dir(self) -> Dir {
-        match self {
-            Dir::North => Dir::South,
-            Dir::South => Dir::North,
-            Dir::West => Dir::East,
-            Dir::East => Dir::West,
-        }
-    }
 }
 
 impl From<Dir> for isize {
@@ -76,71 +70,76 @@ type Area = HashMap<Coords, Tile>;
 fn make_move(c: &mut Computer, move_to: Dir) -> ProblemResult<Output> {
     c.set_stdin(move_to.into());
 
-    Ok(loop {
-        // wait until droid finishes its movement and get its output
+    // wait until droid finishes its movement and get its output
+    let status = c.run_until_output(1)?[0];
 
-        c.step()?;
+    // and until it asks for the next move, so every copy of it is ready for input
+    c.run()?;
 
-        match c.get_output() {
-            Ok(x) => break x,
-            Err(_) => {
-                // no output yet, droid is still moving
-                continue;
-            }
-        }
-    }
-    .into())
+    Ok(status.into())
 }
 
-fn rec(
-    c: &mut Computer,
-    program: &[isize],
-    cur_pos: Coords,
-    visited: &mut Area,
-    moves_cnt: usize,
-) -> ProblemResult<usize> {
-    let mut min_score = std::usize::MAX;
-
-    for dir in DIRS.iter() {
-        let new_coords = dir.to_coords(cur_pos);
-        if visited.contains_key(&new_coords) && visited[&new_coords].moves_cnt <= (moves_cnt + 1) {
-            continue;
-        }
-
-        // move forward
-        let tile = make_move(c, *dir)?;
-
-        visited.insert(new_coords, Tile { moves_cnt, tile });
-
-        if tile == Output::Wall {
-            // drone position isn't changed if it hits a wall
-            continue;
-        }
+// Breadth first search over machine states. Every reached position keeps its own
+// copy of the droid, so the droid never has to walk back and the first time the
+// oxygen system is reached is along the shortest path.
+fn explore(program: &[isize]) -> ProblemResult<(Option<usize>, Area)> {
+    let mut c = Computer::new(program, None);
+    let mut visited: Area = HashMap::new();
+    let mut oxygen = None;
+
+    // run until the droid asks for the first move
+    c.run()?;
+
+    visited.insert(
+        (0, 0),
+        Tile {
+            moves_cnt: 0,
+            tile: Output::Moved,
+        },
+    );
+
+    let mut q = VecDeque::new();
+    q.push_back(((0, 0), c, 0));
+
+    while let Some((cur_pos, droid, moves_cnt)) = q.pop_front() {
+        for dir in DIRS.iter() {
+            let new_coords = dir.to_coords(cur_pos);
+            if visited.contains_key(&new_coords) {
+                continue;
+            }
 
-        if tile == Output::Oxygen {
-            min_score = moves_cnt + 1;
-        }
+            let mut forked = droid.clone();
+            let tile = make_move(&mut forked, *dir)?;
+
+            visited.insert(
+                new_coords,
+                Tile {
+                    moves_cnt: moves_cnt + 1,
+                    tile,
+                },
+            );
+
+            match tile {
+                // drone position isn't changed if it hits a wall
+                Output::Wall => continue,
+                Output::Oxygen if oxygen.is_none() => oxygen = Some(moves_cnt + 1),
+                _ => {}
+            }
 
-        let score = rec(c, program, new_coords, visited, moves_cnt + 1)?;
-        if score < min_score {
-            min_score = score;
+            q.push_back((new_coords, forked, moves_cnt + 1));
         }
-
-        // rewind back
-        make_move(c, dir.opposite_dir())?;
     }
 
-    Ok(min_score)
+    Ok((oxygen, visited))
 }
 
 fn first_star(program: &[isize]) -> ProblemResult<(usize, Area)> {
-    let mut c = Computer::new(program, None);
-    let mut visited: Area = HashMap::new();
+    let (oxygen, visited) = explore(program)?;
 
-    c.step()?;
-    let res = rec(&mut c, &program, (0, 0), &mut visited, 0)?;
-
-    Ok((res, visited))
+    match oxygen {
+        Some(moves_cnt) => Ok((moves_cnt, visited)),
+        None => Err(format_err!("Oxygen system is unreachable")),
+    }
 }
 
 fn get_vicinity(map: &Area, coords: Coords) -> VecDeque<Coords> {
@@ -202,22 +201,26 @@ fn second_star(map: &Area) -> ProblemResult<usize> {
     Ok(iters)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
 
-    let (r1, visited) = first_star(&input)?;
-    let r2 = second_star(&visited);
+pub(crate) struct Problem;
 
-    assert_eq!(r1, 282);
-    assert_eq!(*r2.as_ref().unwrap(), 286);
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
 
-    Ok(
-        RetTypes::Usize(
-            result(
-                Ok(r1),
-                r2
-            )
-        )
-    )
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<usize> {
+        Ok(first_star(program)?.0)
+    }
+
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<usize> {
+        // the map explored by the first star is required by the second one
+        let (_, visited) = first_star(program)?;
+        second_star(&visited)
+    }
 }
@@ -1,8 +1,7 @@
 use std::cmp::{min, Ordering};
 
-use failure::Error;
-
-use utils::{make_number, number_to_string, result, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{make_number, number_to_string, ParseResult, ProblemResult};
 
 const BASE_PAT: [isize; 4] = [0, 1, 0, -1];
 
@@ -51,7 +50,7 @@ fn multiply(xs: &[isize], sums: &[isize], group_len: usize) -> isize {
     net
 }
 
-fn make_part_sums(digits: &[isize], sums: &mut Vec<isize>) {
+fn make_part_sums(digits: &[isize], sums: &mut [isize]) {
     for idx in 0..digits.len() {
         sums[idx] = digits[idx] + if idx == 0 { 0 } else { sums[idx - 1] };
     }
@@ -106,20 +105,24 @@ fn second_star(input: &str, phases: usize, times: usize) -> ProblemResult<String
     Ok(number_to_string(&tmp))
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = input_raw.trim();
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
+
+impl Solution for Problem {
+    type Input = String;
+    type First = String;
+    type Second = String;
 
-    let r1 = first_star(input, 100);
-    // answer must be a string because of possible leading zeros
-    assert_eq!(*r1.as_ref().unwrap(), "36627552");
+    fn parse(&self, input_raw: &str) -> ParseResult<String> {
+        Ok(input_raw.trim().to_owned())
+    }
 
-    let r2 = second_star(input, 100, 10000);
-    assert_eq!(*r2.as_ref().unwrap(), "79723033");
+    fn part1(&self, input: &String) -> ProblemResult<String> {
+        first_star(input, 100)
+    }
 
-    Ok(
-        RetTypes::String(
-            result(r1, r2)
-        )
-    )
+    fn part2(&self, input: &String) -> ProblemResult<String> {
+        second_star(input, 100, 10000)
+    }
 }
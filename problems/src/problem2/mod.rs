@@ -1,28 +1,27 @@
-use failure::{format_err, Error};
+use failure::format_err;
 
 use crate::computer::{parse_intcode, Computer};
-use utils::{result, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{ParseResult, ProblemResult};
+
+fn first_star(program: &[isize]) -> ProblemResult<isize> {
+    let mut program = program.to_vec();
 
-fn first_star(program: &mut [isize]) -> ProblemResult<isize> {
     // input for the program
     program[1] = 12;
     program[2] = 2;
 
-    let mut c = Computer::new(program, None);
+    let mut c = Computer::new(&program, None);
 
     // run the program
-    c.step()?;
+    c.run_until_halt()?;
 
     Ok(c.get_cell(0))
 }
 
-fn second_star(program: &mut [isize]) -> ProblemResult<isize> {
-    let mut saved_program: Vec<isize> = vec![0; program.len()];
-
-    saved_program.clone_from_slice(&program);
-
+fn second_star(saved_program: &[isize]) -> ProblemResult<isize> {
     for n in 0..10000isize {
-        let program = &mut saved_program.clone();
+        let program = &mut saved_program.to_vec();
 
         let (noun, verb) = (n % 100, n / 100);
 
@@ -32,7 +31,7 @@ fn second_star(program: &mut [isize]) -> ProblemResult<isize> {
 
         let mut c = Computer::new(program, None);
 
-        c.step()?;
+        c.run_until_halt()?;
 
         if c.get_cell(0) == 19_690_720 {
             return Ok(100 * noun + verb);
@@ -42,19 +41,24 @@ fn second_star(program: &mut [isize]) -> ProblemResult<isize> {
     Err(format_err!("Couldn't find appropriate solution!"))
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let mut input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
 
-    let r1 = first_star(&mut input);
-    let r2 = second_star(&mut input);
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = isize;
+    type Second = isize;
 
-    assert_eq!(*r1.as_ref().unwrap(), 3_706_713);
-    assert_eq!(*r2.as_ref().unwrap(), 8609);
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
+
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        first_star(program)
+    }
 
-    Ok(
-        RetTypes::Isize(
-            result(r1, r2)
-        )
-    )
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        second_star(program)
+    }
 }
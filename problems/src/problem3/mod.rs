@@ -1,9 +1,8 @@
-use failure::{format_err, Error};
+use failure::format_err;
 use std::collections::{HashMap, HashSet};
 
-use utils::{
-    man_dist_2d, result, split_by_comma, split_by_lines, ParseResult, ProblemResult, RetTypes
-};
+use crate::solution::Solution;
+use utils::{man_dist_2d, split_by_comma, split_by_lines, ParseResult, ProblemResult};
 
 #[derive(Hash, PartialEq, Eq, Copy, Clone)]
 enum Dir {
@@ -13,7 +12,7 @@ enum Dir {
     Down,
 }
 
-struct Op {
+pub(crate) struct Op {
     dir: Dir,
     steps: usize,
 }
@@ -55,7 +54,7 @@ fn make_pair(item: &str) -> ParseResult<Op> {
     })
 }
 
-fn advance_cursor(mut cursor: &mut Cursor, dir: Dir) {
+fn advance_cursor(cursor: &mut Cursor, dir: Dir) {
     match dir {
         Dir::Left => cursor.coords.x -= 1,
         Dir::Right => cursor.coords.x += 1,
@@ -79,7 +78,7 @@ fn make_moves(
 
         points
             .entry(cursor.coords)
-            .or_insert_with(HashSet::new)
+            .or_default()
             .insert(wire_no);
 
         lengths
@@ -94,7 +93,13 @@ fn make_moves(
     intersections
 }
 
-fn solve_both_stars(wires: &[Vec<Op>]) -> ProblemResult<(usize, usize)> {
+struct Wires {
+    overlaps: HashSet<Point>,
+    // maps (wire_no, point) to the length of wire required to get to the point
+    lengths: HashMap<(usize, Point), usize>,
+}
+
+fn lay_wires(wires: &[Vec<Op>]) -> Wires {
     let mut points = HashMap::new();
     let mut lengths = HashMap::new();
 
@@ -114,21 +119,34 @@ fn solve_both_stars(wires: &[Vec<Op>]) -> ProblemResult<(usize, usize)> {
         }
     }
 
-    // find intersection with the minimum Manhattan distance (part 1)
+    Wires { overlaps, lengths }
+}
 
-    let min_point = overlaps.iter().min_by_key(|p| man_dist_2d(p.x, p.y, 0, 0));
+fn first_star(wires: &[Vec<Op>]) -> ProblemResult<usize> {
+    // find intersection with the minimum Manhattan distance
 
-    if min_point.is_none() {
-        return Err(format_err!("Couldn't find appropriate solution!"));
+    let laid = lay_wires(wires);
+
+    match laid.overlaps.iter().min_by_key(|p| man_dist_2d(p.x, p.y, 0, 0)) {
+        Some(min_point) => Ok(man_dist_2d(min_point.x, min_point.y, 0, 0) as usize),
+        None => Err(format_err!("Couldn't find appropriate solution!")),
     }
+}
+
+fn second_star(wires: &[Vec<Op>]) -> ProblemResult<usize> {
+    // find intersection with the minimum wires length
+
+    let laid = lay_wires(wires);
 
-    // find intersection with the minimum wires length (part 2)
+    if laid.overlaps.is_empty() {
+        return Err(format_err!("Couldn't find appropriate solution!"));
+    }
 
-    let mut min_total_len = std::usize::MAX;
+    let mut min_total_len = usize::MAX;
 
-    for p in overlaps.iter() {
+    for p in laid.overlaps.iter() {
         let net_length = (0..wires.len())
-            .map(|wire_no| lengths[&(wire_no, *p)])
+            .map(|wire_no| laid.lengths[&(wire_no, *p)])
             .sum();
 
         if min_total_len > net_length {
@@ -136,27 +154,30 @@ fn solve_both_stars(wires: &[Vec<Op>]) -> ProblemResult<(usize, usize)> {
         }
     }
 
-    let min_point = min_point.unwrap();
-
-    Ok((
-        man_dist_2d(min_point.x, min_point.y, 0, 0) as usize,
-        min_total_len,
-    ))
+    Ok(min_total_len)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
 
-    let tmp: Result<Vec<_>, _> = split_by_lines(input_raw, &|e: &str| Ok(e.to_owned()))?
-        .iter()
-        .map(|line| split_by_comma(line, &|e: &str| make_pair(e)))
-        .collect();
+impl Solution for Problem {
+    type Input = Vec<Vec<Op>>;
+    type First = usize;
+    type Second = usize;
 
-    let solutions = solve_both_stars(&tmp?)?;
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<Vec<Op>>> {
+        split_by_lines(input_raw, &|e: &str| Ok(e.to_owned()))?
+            .iter()
+            .map(|line| split_by_comma(line, &|e: &str| make_pair(e)))
+            .collect()
+    }
+
+    fn part1(&self, wires: &Vec<Vec<Op>>) -> ProblemResult<usize> {
+        first_star(wires)
+    }
 
-    Ok(
-        RetTypes::Usize(
-            result(Ok(solutions.0), Ok(solutions.1))
-        )
-    )
+    fn part2(&self, wires: &Vec<Vec<Op>>) -> ProblemResult<usize> {
+        second_star(wires)
+    }
 }
@@ -1,10 +1,13 @@
-use failure::Error;
+use failure::format_err;
 use itertools::chain;
 
-use utils::{result, split_digits, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{split_digits, ParseResult, ProblemResult};
 
-const LOWER_BOUND: usize = 178_416;
-const UPPER_BOUND: usize = 676_461;
+pub(crate) struct Range {
+    lower: usize,
+    upper: usize,
+}
 
 fn test_non_decr(xs: &[usize]) -> bool {
     for idx in 0..xs.len() {
@@ -58,8 +61,8 @@ fn pred(n: usize, p1: &dyn Fn(&[usize]) -> bool, p2: &dyn Fn(&[usize]) -> bool)
     p1(&digits) && p2(&digits)
 }
 
-fn first_star() -> ProblemResult<usize> {
-    let counter = (LOWER_BOUND..=UPPER_BOUND)
+fn first_star(range: &Range) -> ProblemResult<usize> {
+    let counter = (range.lower..=range.upper)
         .filter_map(|n| {
             if pred(n, &test_non_decr, &test_adj) {
                 Some(1)
@@ -72,8 +75,8 @@ fn first_star() -> ProblemResult<usize> {
     Ok(counter)
 }
 
-fn second_star() -> ProblemResult<usize> {
-    let counter = (LOWER_BOUND..=UPPER_BOUND)
+fn second_star(range: &Range) -> ProblemResult<usize> {
+    let counter = (range.lower..=range.upper)
         .filter_map(|n| {
             if pred(n, &test_non_decr, &test_adj_two) {
                 Some(1)
@@ -86,10 +89,38 @@ fn second_star() -> ProblemResult<usize> {
     Ok(counter)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    Ok(
-        RetTypes::Usize(
-            result(first_star(), second_star())
-        )
-    )
+fn parse_range(input_raw: &str) -> ParseResult<Range> {
+    // input is given as "lower-upper"
+    let parts: Vec<&str> = input_raw.trim().split('-').collect();
+
+    if parts.len() != 2 {
+        return Err(format_err!("Invalid range {}", input_raw));
+    }
+
+    Ok(Range {
+        lower: parts[0].parse::<usize>()?,
+        upper: parts[1].parse::<usize>()?,
+    })
+}
+
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
+
+impl Solution for Problem {
+    type Input = Range;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Range> {
+        parse_range(input_raw)
+    }
+
+    fn part1(&self, range: &Range) -> ProblemResult<usize> {
+        first_star(range)
+    }
+
+    fn part2(&self, range: &Range) -> ProblemResult<usize> {
+        second_star(range)
+    }
 }
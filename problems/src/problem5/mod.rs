@@ -1,7 +1,7 @@
-use failure::Error;
-use utils::{result, ProblemResult, RetTypes};
+use utils::{ParseResult, ProblemResult};
 
 use crate::computer::{consume_until_break, parse_intcode, Computer};
+use crate::solution::Solution;
 
 fn first_star(program: &[isize]) -> ProblemResult<Vec<isize>> {
     let mut c = Computer::new(program, Some(vec![1]));
@@ -13,22 +13,24 @@ fn second_star(program: &[isize]) -> ProblemResult<isize> {
     Ok(consume_until_break(&mut c).unwrap()[0])
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
 
-    let r1 = first_star(&input);
-    let r2 = second_star(&input);
+pub(crate) struct Problem;
 
-    assert_eq!(
-        *r1.as_ref().unwrap(),
-        vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 6_731_945]
-    );
-    assert_eq!(*r2.as_ref().unwrap(), 9_571_668);
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = Vec<isize>;
+    type Second = isize;
 
-    Ok(
-        RetTypes::VecIsizeIsize(
-            result(r1, r2)
-        )
-    )
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
+
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<Vec<isize>> {
+        first_star(program)
+    }
+
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        second_star(program)
+    }
 }
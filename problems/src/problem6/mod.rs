@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 
-use failure::{format_err, Error};
-use utils::{result, split_by_lines, ProblemResult, RetTypes};
+use failure::format_err;
 
-struct Orbit {
+use crate::solution::Solution;
+use utils::{split_by_lines, ParseResult, ProblemResult};
+
+pub(crate) struct Orbit {
     satellite: String,
     center: String,
 }
@@ -14,7 +16,7 @@ fn traverse(map: &HashMap<&str, &str>, sat: &str) -> Vec<String> {
         return vec!["COM".to_owned()];
     }
 
-    let mut xs = traverse(&map, center);
+    let mut xs = traverse(map, center);
     xs.push(center.to_owned());
 
     xs
@@ -61,20 +63,30 @@ fn second_star(input: &[Orbit]) -> ProblemResult<usize> {
     Err(format_err!("Path not found"))
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
 
-    let input: Vec<Orbit> = split_by_lines(input_raw, &|e: &str| {
-        let tmp = e.split(')').collect::<Vec<&str>>();
-        Ok(Orbit {
-            center: tmp[0].to_owned(),
-            satellite: tmp[1].to_owned(),
+impl Solution for Problem {
+    type Input = Vec<Orbit>;
+    type First = usize;
+    type Second = usize;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<Orbit>> {
+        split_by_lines(input_raw, &|e: &str| {
+            let tmp = e.split(')').collect::<Vec<&str>>();
+            Ok(Orbit {
+                center: tmp[0].to_owned(),
+                satellite: tmp[1].to_owned(),
+            })
         })
-    })?;
+    }
 
-    Ok(
-        RetTypes::Usize(
-            result(first_star(&input), second_star(&input))
-        )
-    )
+    fn part1(&self, input: &Vec<Orbit>) -> ProblemResult<usize> {
+        first_star(input)
+    }
+
+    fn part2(&self, input: &Vec<Orbit>) -> ProblemResult<usize> {
+        second_star(input)
+    }
 }
@@ -1,8 +1,8 @@
-use failure::Error;
 use permutohedron as ph;
 
-use crate::computer::{parse_intcode, Computer};
-use utils::{result, ProblemResult, RetTypes};
+use crate::computer::{parse_intcode, Computer, Event};
+use crate::solution::Solution;
+use utils::{ParseResult, ProblemResult};
 
 const AMPLIFIERS: isize = 5;
 
@@ -14,14 +14,13 @@ fn first_star(program: &[isize]) -> ProblemResult<isize> {
 
     for perm in perms {
         let mut c = Computer::new(program, Some(vec![perm[0], 0]));
-        c.step()?;
+        let mut final_val = c.run_until_output(1)?[0];
 
         for j in perm.iter().skip(1) {
-            c = Computer::new(program, Some(vec![*j, c.get_output()?]));
-            c.step()?;
+            c = Computer::new(program, Some(vec![*j, final_val]));
+            final_val = c.run_until_output(1)?[0];
         }
 
-        let final_val = c.get_output()?;
         if final_val > best_val {
             best_val = final_val;
         }
@@ -31,16 +30,14 @@ fn first_star(program: &[isize]) -> ProblemResult<isize> {
 }
 
 fn exec_amp(amp: &mut Computer, input: isize) -> ProblemResult<(bool, isize)> {
-    amp.step()?;
-
-    if amp.is_finished() {
-        return Ok((true, 0));
-    } else if amp.waits_input() {
-        amp.set_stdin(input);
-        amp.step()?;
-    }
-
-    Ok((false, amp.get_output().unwrap()))
+    Ok(match amp.run()? {
+        Event::Halted => (true, 0),
+        Event::NeedInput => {
+            amp.set_stdin(input);
+            (false, amp.run_until_output(1)?[0])
+        }
+        Event::Output(value) => (false, value),
+    })
 }
 
 fn second_star(program: &[isize]) -> ProblemResult<isize> {
@@ -76,19 +73,24 @@ fn second_star(program: &[isize]) -> ProblemResult<isize> {
     Ok(best_val)
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
 
-    let r1 = first_star(&input);
-    let r2 = second_star(&input);
+pub(crate) struct Problem;
 
-    assert_eq!(*r1.as_ref().unwrap(), 38_500);
-    assert_eq!(*r2.as_ref().unwrap(), 33_660_560);
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = isize;
+    type Second = isize;
 
-    Ok(
-        RetTypes::Isize(
-            result(r1, r2)
-        )
-    )
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
+
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        first_star(program)
+    }
+
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        second_star(program)
+    }
 }
@@ -1,7 +1,5 @@
-use image;
-
-use failure::Error;
-use utils::{result, split_by, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{split_by, ParseResult, ProblemResult};
 
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
@@ -13,8 +11,8 @@ const TRANSP: usize = 2;
 fn first_star(input: &[usize]) -> ProblemResult<usize> {
     let area = WIDTH * HEIGHT;
 
-    let mut zeros = std::usize::MAX;
-    let mut prev_zeros = std::usize::MAX;
+    let mut zeros = usize::MAX;
+    let mut prev_zeros = usize::MAX;
 
     let mut ones = 0;
     let mut twos = 0;
@@ -49,8 +47,7 @@ fn get_pixel(input: &[usize], idx: usize) -> usize {
         .iter()
         .skip(idx)
         .step_by(WIDTH * HEIGHT)
-        .skip_while(|e| **e == TRANSP)
-        .next()
+        .find(|e| **e != TRANSP)
     {
         Some(e) => *e,
         None => TRANSP,
@@ -74,14 +71,24 @@ fn second_star(input: &[usize]) -> ProblemResult<String> {
     Ok(filename.to_owned())
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = split_by(input_raw, "", &|e: &str| Ok(e.parse::<usize>()?))?;
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
 
-    Ok(
-        RetTypes::UsizeString(
-            result(first_star(&input), second_star(&input))
-        )
-    )
+impl Solution for Problem {
+    type Input = Vec<usize>;
+    type First = usize;
+    type Second = String;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<usize>> {
+        split_by(input_raw, "", &|e: &str| Ok(e.parse::<usize>()?))
+    }
 
+    fn part1(&self, input: &Vec<usize>) -> ProblemResult<usize> {
+        first_star(input)
+    }
+
+    fn part2(&self, input: &Vec<usize>) -> ProblemResult<String> {
+        second_star(input)
+    }
 }
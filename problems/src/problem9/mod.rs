@@ -1,31 +1,35 @@
-use failure::Error;
-
 use crate::computer::{parse_intcode, Computer};
-use utils::{result, ProblemResult, RetTypes};
+use crate::solution::Solution;
+use utils::{ParseResult, ProblemResult};
 
 fn first_star(program: &[isize]) -> ProblemResult<isize> {
     let mut c = Computer::new(program, Some(vec![1]));
-    c.step()?;
-    Ok(c.get_output()?)
+    Ok(c.run_until_output(1)?[0])
 }
 
 fn second_star(program: &[isize]) -> ProblemResult<isize> {
     let mut c = Computer::new(program, Some(vec![2]));
-    c.step()?;
-    Ok(c.get_output()?)
+    Ok(c.run_until_output(1)?[0])
 }
 
-pub(crate) fn solve() -> Result<RetTypes, Error> {
-    let input_raw = include_str!("./input");
-    let input = parse_intcode(input_raw)?;
+pub(crate) const INPUT: &str = include_str!("./input");
+
+pub(crate) struct Problem;
+
+impl Solution for Problem {
+    type Input = Vec<isize>;
+    type First = isize;
+    type Second = isize;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Vec<isize>> {
+        parse_intcode(input_raw)
+    }
 
-    Ok(
-        RetTypes::Isize(
-            result(
-                first_star(&input),
-                second_star(&input),
-            )
-        )
-    )
+    fn part1(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        first_star(program)
+    }
 
+    fn part2(&self, program: &Vec<isize>) -> ProblemResult<isize> {
+        second_star(program)
+    }
 }
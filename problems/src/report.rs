@@ -0,0 +1,125 @@
+use failure::{format_err, Error};
+use serde::Serialize;
+
+use utils::{StarResult, Stars};
+
+use crate::solution::Solved;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Format {
+    // human readable colored output
+    Text,
+    // one JSON object per line
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub(crate) fn parse(s: &str) -> Result<Format, Error> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format_err!("Unknown output format `{}`", s)),
+        }
+    }
+}
+
+pub(crate) const CSV_HEADER: &str = "day,star,answer,error,elapsed_ms,parse_us,star_us,worker";
+
+// result of one star of one problem
+#[derive(Debug, Serialize)]
+pub(crate) struct Record {
+    pub day: usize,
+    pub star: usize,
+    pub answer: Option<String>,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+    // time spent in parsing and in the star itself, unknown if the problem failed as a whole
+    pub parse_us: Option<u64>,
+    pub star_us: Option<u64>,
+    pub worker: usize,
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+impl Record {
+    pub(crate) fn to_json(&self) -> String {
+        // the record consists of plain strings and numbers only
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub(crate) fn to_csv(&self) -> String {
+        [
+            self.day.to_string(),
+            self.star.to_string(),
+            csv_field(self.answer.as_deref().unwrap_or("")),
+            csv_field(self.error.as_deref().unwrap_or("")),
+            self.elapsed_ms.to_string(),
+            self.parse_us.map(|us| us.to_string()).unwrap_or_default(),
+            self.star_us.map(|us| us.to_string()).unwrap_or_default(),
+            self.worker.to_string(),
+        ]
+        .join(",")
+    }
+}
+
+fn selected(stars: Stars) -> Vec<usize> {
+    match stars {
+        Stars::Both => vec![1, 2],
+        Stars::First => vec![1],
+        Stars::Second => vec![2],
+    }
+}
+
+// turns result of a problem into records, one for every computed star
+pub(crate) fn make_records(
+    day: usize,
+    stars: Stars,
+    result: &Result<Solved, Error>,
+    elapsed_ms: u64,
+    worker: usize,
+) -> Vec<Record> {
+    let record = |star, answer, error, solved: Option<&Solved>| Record {
+        day,
+        star,
+        answer,
+        error,
+        elapsed_ms,
+        parse_us: solved.map(|s| s.timings.parse.as_micros() as u64),
+        star_us: solved
+            .and_then(|s| s.timings.star(star))
+            .map(|d| d.as_micros() as u64),
+        worker,
+    };
+
+    match result {
+        // problem failed as a whole, so all the requested stars failed
+        Err(err) => selected(stars)
+            .into_iter()
+            .map(|star| record(star, None, Some(err.to_string()), None))
+            .collect(),
+
+        Ok(solved) => solved
+            .answer
+            .stars()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, star)| match star {
+                StarResult::Skipped => None,
+                StarResult::Solved(answer) => {
+                    Some(record(idx + 1, Some(answer.to_string()), None, Some(solved)))
+                }
+                StarResult::Failed(err) => {
+                    Some(record(idx + 1, None, Some(err.clone()), Some(solved)))
+                }
+            })
+            .collect(),
+    }
+}
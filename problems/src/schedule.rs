@@ -0,0 +1,69 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::{versioned, Task};
+
+const HISTORY_VERSION: u32 = 1;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Schedule {
+    // problems start in the order they were selected
+    InOrder,
+    // problems which took longer last time start first, so that the slowest
+    // one doesn't start last and dominate the total time
+    LongestFirst,
+}
+
+impl Schedule {
+    pub(crate) fn parse(s: &str) -> Result<Schedule, Error> {
+        match s {
+            "in-order" => Ok(Schedule::InOrder),
+            "longest-first" => Ok(Schedule::LongestFirst),
+            _ => Err(format_err!("Unknown schedule `{}`", s)),
+        }
+    }
+}
+
+// run times of previous runs in millis by day
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct History {
+    version: u32,
+    elapsed: BTreeMap<usize, u64>,
+}
+
+impl History {
+    // missing file is not an error, there is simply no history yet
+    pub(crate) fn load(path: &Path) -> Result<History, Error> {
+        if !path.exists() {
+            return Ok(History {
+                version: HISTORY_VERSION,
+                elapsed: BTreeMap::new(),
+            });
+        }
+
+        versioned::load(path, "timings", HISTORY_VERSION, |history: &History| {
+            history.version
+        })
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|err| format_err!("Can't write timings {}: {}", path.display(), err))
+    }
+
+    pub(crate) fn record(&mut self, day: usize, elapsed: u64) {
+        self.elapsed.insert(day, elapsed);
+    }
+
+    // reorders tasks according to the schedule, days which never ran
+    // are considered the slowest ones
+    pub(crate) fn arrange(&self, tasks: &mut [Task], schedule: Schedule) {
+        if schedule == Schedule::LongestFirst {
+            tasks.sort_by_key(|(_, _, day)| {
+                std::cmp::Reverse(self.elapsed.get(day).copied().unwrap_or(u64::MAX))
+            });
+        }
+    }
+}
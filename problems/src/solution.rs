@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use failure::Error;
+use serde::Serialize;
+
+use utils::{result, Answers, ParseResult, ProblemResult, Stars};
+
+// Every problem is solved in three separate stages, so that the runner can tell
+// how much time is spent on each of them. Stars get their own copy of input if
+// they need to modify it and must not rely on each other.
+pub(crate) trait Solution {
+    type Input;
+    type First;
+    type Second;
+
+    fn parse(&self, input_raw: &str) -> ParseResult<Self::Input>;
+
+    fn part1(&self, input: &Self::Input) -> ProblemResult<Self::First>;
+
+    fn part2(&self, input: &Self::Input) -> ProblemResult<Self::Second>;
+}
+
+// time spent in every stage, None if the star wasn't requested
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct Timings {
+    pub parse: Duration,
+    pub part1: Option<Duration>,
+    pub part2: Option<Duration>,
+}
+
+impl Timings {
+    pub(crate) fn star(&self, star: usize) -> Option<Duration> {
+        if star == 1 {
+            self.part1
+        } else {
+            self.part2
+        }
+    }
+}
+
+pub(crate) struct Solved {
+    pub answer: Answers,
+    pub timings: Timings,
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let now = Instant::now();
+    let res = f();
+    (res, now.elapsed())
+}
+
+// object safe counterpart of Solution used by the runner
+pub(crate) trait Runnable: Sync {
+    fn run(&self, input_raw: &str, stars: Stars) -> Result<Solved, Error>;
+}
+
+impl<S> Runnable for S
+where
+    S: Solution + Sync,
+    S::First: Serialize,
+    S::Second: Serialize,
+{
+    fn run(&self, input_raw: &str, stars: Stars) -> Result<Solved, Error> {
+        let mut timings = Timings::default();
+
+        let (input, elapsed) = timed(|| self.parse(input_raw));
+        timings.parse = elapsed;
+        let input = input?;
+
+        let first = if stars != Stars::Second {
+            let (res, elapsed) = timed(|| self.part1(&input));
+            timings.part1 = Some(elapsed);
+            Some(res)
+        } else {
+            None
+        };
+
+        let second = if stars != Stars::First {
+            let (res, elapsed) = timed(|| self.part2(&input));
+            timings.part2 = Some(elapsed);
+            Some(res)
+        } else {
+            None
+        };
+
+        Ok(Solved {
+            answer: result(first, second),
+            timings,
+        })
+    }
+}
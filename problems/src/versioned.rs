@@ -0,0 +1,32 @@
+use std::{fs, path::Path};
+
+use failure::{format_err, Error};
+use serde::de::DeserializeOwned;
+
+// Reads a JSON file which records the version of its format, `what` names the
+// file in errors and `version` picks the version out of the parsed contents
+pub(crate) fn load<T: DeserializeOwned>(
+    path: &Path,
+    what: &str,
+    expected: u32,
+    version: impl FnOnce(&T) -> u32,
+) -> Result<T, Error> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format_err!("Can't read {} {}: {}", what, path.display(), err))?;
+
+    let contents: T = serde_json::from_str(&raw)
+        .map_err(|err| format_err!("Invalid {} {}: {}", what, path.display(), err))?;
+
+    let found = version(&contents);
+    if found != expected {
+        return Err(format_err!(
+            "Unsupported version {} of {} {}, expected {}",
+            found,
+            what,
+            path.display(),
+            expected
+        ));
+    }
+
+    Ok(contents)
+}
@@ -0,0 +1,109 @@
+use std::fmt::{self, Display, Formatter};
+
+use colored::*;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ProblemResult;
+
+// Answer of a single star. Solutions may return any serializable type, it's
+// converted into a JSON value right away, so the runner doesn't have to know
+// about all the types returned by different days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer(Value);
+
+impl Answer {
+    pub fn new<T: Serialize>(answer: &T) -> ProblemResult<Answer> {
+        Ok(Answer(serde_json::to_value(answer)?))
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.0
+    }
+}
+
+fn render(value: &Value, f: &mut Formatter<'_>) -> fmt::Result {
+    match value {
+        // strings are printed without quotes
+        Value::String(s) => write!(f, "{}", s),
+        Value::Array(items) => {
+            write!(f, "[")?;
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                render(item, f)?;
+            }
+            write!(f, "]")
+        }
+        other => write!(f, "{}", other),
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        render(&self.0, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StarResult {
+    Skipped,
+    Solved(Answer),
+    Failed(String),
+}
+
+impl StarResult {
+    // None means the star wasn't requested
+    pub fn new<T: Serialize>(answer: Option<ProblemResult<T>>) -> StarResult {
+        match answer.map(|res| res.and_then(|answer| Answer::new(&answer))) {
+            Some(Ok(answer)) => StarResult::Solved(answer),
+            Some(Err(err)) => StarResult::Failed(err.to_string()),
+            None => StarResult::Skipped,
+        }
+    }
+}
+
+impl Display for StarResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StarResult::Skipped => write!(f, "{}", "skipped".dimmed()),
+            StarResult::Solved(answer) => write!(f, "{}", answer),
+            StarResult::Failed(err) => write!(f, "{}: {}", "error".red(), err),
+        }
+    }
+}
+
+// answers for both stars of a problem
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answers {
+    stars: [StarResult; 2],
+}
+
+impl Answers {
+    pub fn stars(&self) -> &[StarResult; 2] {
+        &self.stars
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}\n{}: {}",
+            "first star solution".blue(),
+            self.stars[0],
+            "second star solution".yellow(),
+            self.stars[1]
+        )
+    }
+}
+
+pub fn result<T: Serialize, K: Serialize>(
+    basic: Option<ProblemResult<T>>,
+    adv: Option<ProblemResult<K>>,
+) -> Answers {
+    Answers {
+        stars: [StarResult::new(basic), StarResult::new(adv)],
+    }
+}
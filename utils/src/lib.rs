@@ -1,10 +1,6 @@
 use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub};
-use std::{
-    fmt::{self, Debug, Display, Formatter},
-    string::ToString,
-};
+use std::string::ToString;
 
-use colored::*;
 use failure::Error;
 use num::FromPrimitive;
 use num_traits::{sign::Signed, CheckedDiv, Num};
@@ -12,27 +8,15 @@ use num_traits::{sign::Signed, CheckedDiv, Num};
 pub type ProblemResult<T> = Result<T, Error>;
 pub type ParseResult<T> = Result<T, Error>;
 
-mod ret_types;
-pub use ret_types::RetTypes;
+mod answer;
+pub use answer::{result, Answer, Answers, StarResult};
 
-pub struct Ret<T, K> {
-    answer_basic: ProblemResult<T>,
-    answer_adv: ProblemResult<K>,
-}
-
-pub type RetOne<T> = Ret<T, T>;
-
-impl<T: Debug, K: Debug> Display for Ret<T, K> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}: {:?}\n{}: {:?}",
-            "first star solution".blue(),
-            self.answer_basic,
-            "second star solution".yellow(),
-            self.answer_adv
-        )
-    }
+// which stars of a problem should be computed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Stars {
+    Both,
+    First,
+    Second,
 }
 
 pub fn man_dist_2d<T: Add<Output = T> + Sub<Output = T> + Signed>(x1: T, y1: T, x2: T, y2: T) -> T {
@@ -103,13 +87,6 @@ where
     (n[0], n[1])
 }
 
-pub fn result<T: Debug, K: Debug>(basic: ProblemResult<T>, adv: ProblemResult<K>) -> Ret<T, K> {
-    Ret {
-        answer_basic: basic,
-        answer_adv: adv,
-    }
-}
-
 pub fn split_by_lines<T>(input: &str, f: &dyn Fn(&str) -> ParseResult<T>) -> ParseResult<Vec<T>> {
     split_by(input, "\n", f)
 }
@@ -126,7 +103,7 @@ pub fn split_by<T>(
     let res: ParseResult<Vec<_>> = input
         .split(sep)
         .filter(|item| item != &"")
-        .map(|item| f(item))
+        .map(f)
         .collect();
 
     res